@@ -73,7 +73,7 @@ async fn tokio_main() -> Result<(), Box<dyn Error>> {
                     let _ = process_guest(restaurant, stream).await;
                 });
             },
-            phrase = reco_sub.recognize() => {
+            Some(phrase) = reco_sub.recognize() => {
                 // Extract the index of menu item to be served from the semantic tags in the phrase.
                 let item = *phrase.semantics[0].value.as_int().unwrap() as usize;
                 // Extract the name of the guest to serve from the semantic tags in the phrase.
@@ -0,0 +1,125 @@
+use std::ffi::OsString;
+
+use windows as Windows;
+use Windows::Win32::Foundation::E_INVALIDARG;
+
+use crate::token::{Category, Token};
+use crate::Result;
+
+const INPUT_CATEGORY: &str = r"HKEY_LOCAL_MACHINE\SOFTWARE\Microsoft\Speech\AudioInput";
+const OUTPUT_CATEGORY: &str = r"HKEY_LOCAL_MACHINE\SOFTWARE\Microsoft\Speech\AudioOutput";
+
+/// Specifies whether an audio device records or plays audio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioDirection {
+    /// The device records audio, e.g. a microphone.
+    Input,
+    /// The device plays audio, e.g. speakers or headphones.
+    Output,
+}
+
+impl AudioDirection {
+    fn category_id(self) -> &'static str {
+        match self {
+            Self::Input => INPUT_CATEGORY,
+            Self::Output => OUTPUT_CATEGORY,
+        }
+    }
+
+    // The identifier of a device token starts with the identifier of its category
+    fn of_token_id(id: &str) -> Option<Self> {
+        [Self::Input, Self::Output].into_iter().find(|direction| {
+            let category_id = direction.category_id();
+            id.get(..category_id.len())
+                .map_or(false, |prefix| prefix.eq_ignore_ascii_case(category_id))
+        })
+    }
+}
+
+/// An audio device installed on the system.
+pub struct AudioDevice {
+    pub(crate) token: Token,
+    direction: AudioDirection,
+    is_default: bool,
+}
+
+impl AudioDevice {
+    /// Resolves the device with the given identifier, previously obtained from [`id`](Self::id).
+    /// This allows the choice of a device to be persisted, e.g. in the application settings, and
+    /// restored later. Fails if the identifier doesn't belong to an audio device, or if the device
+    /// is no longer available.
+    pub fn from_id<S: AsRef<str>>(id: S) -> Result<Self> {
+        let id = id.as_ref();
+        let direction = AudioDirection::of_token_id(id).ok_or(E_INVALIDARG)?;
+        let token = Token::new(id)?;
+        let is_default = Category::new(direction.category_id())
+            .and_then(|category| category.default_token_id())
+            .map_or(false, |default_id| default_id == id);
+        Ok(Self {
+            token,
+            direction,
+            is_default,
+        })
+    }
+
+    /// Returns the identifier of this device. The identifier can be stored and later resolved
+    /// with [`from_id`](Self::from_id).
+    pub fn id(&self) -> Option<OsString> {
+        self.token.id().ok()
+    }
+
+    /// Returns the human-readable name of this device.
+    pub fn name(&self) -> Option<OsString> {
+        self.token.description().ok()
+    }
+
+    /// Returns whether this device records or plays audio.
+    pub fn direction(&self) -> AudioDirection {
+        self.direction
+    }
+
+    /// Returns `true` if this is the system's default device of its kind.
+    pub fn is_default(&self) -> bool {
+        self.is_default
+    }
+}
+
+/// If successful, returns an iterator enumerating all the audio input devices on the system.
+pub fn input_devices() -> Result<impl Iterator<Item = AudioDevice>> {
+    devices(AudioDirection::Input)
+}
+
+/// Returns the system's default audio input device.
+pub fn default_input_device() -> Result<AudioDevice> {
+    default_device(AudioDirection::Input)
+}
+
+/// If successful, returns an iterator enumerating all the audio output devices on the system.
+pub fn output_devices() -> Result<impl Iterator<Item = AudioDevice>> {
+    devices(AudioDirection::Output)
+}
+
+/// Returns the system's default audio output device.
+pub fn default_output_device() -> Result<AudioDevice> {
+    default_device(AudioDirection::Output)
+}
+
+fn devices(direction: AudioDirection) -> Result<impl Iterator<Item = AudioDevice>> {
+    let category = Category::new(direction.category_id())?;
+    let default_id = category.default_token_id().ok();
+    let tokens = category.enum_tokens(None::<&str>, None)?;
+
+    Ok(tokens.map(move |token| AudioDevice {
+        is_default: default_id.is_some() && token.id().ok() == default_id,
+        token,
+        direction,
+    }))
+}
+
+fn default_device(direction: AudioDirection) -> Result<AudioDevice> {
+    Ok(AudioDevice {
+        token: Category::new(direction.category_id())?.default_token()?,
+        direction,
+        is_default: true,
+    })
+}
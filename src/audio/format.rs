@@ -43,7 +43,43 @@ pub struct AudioFormat {
     pub channels: Channels,
 }
 
+impl SampleRate {
+    fn from_hz(hz: u32) -> Option<Self> {
+        match hz {
+            8000 => Some(Self::Hz8000),
+            11025 => Some(Self::Hz11025),
+            12000 => Some(Self::Hz12000),
+            16000 => Some(Self::Hz16000),
+            22050 => Some(Self::Hz22050),
+            24000 => Some(Self::Hz24000),
+            32000 => Some(Self::Hz32000),
+            44100 => Some(Self::Hz44100),
+            48000 => Some(Self::Hz48000),
+            _ => None,
+        }
+    }
+}
+
 impl AudioFormat {
+    pub(crate) fn from_sapi(sapi_fmt: &WAVEFORMATEX) -> Option<Self> {
+        if sapi_fmt.wFormatTag as u32 != WAVE_FORMAT_PCM {
+            return None;
+        }
+        Some(Self {
+            sample_rate: SampleRate::from_hz(sapi_fmt.nSamplesPerSec)?,
+            bit_rate: match sapi_fmt.wBitsPerSample {
+                8 => BitRate::Bits8,
+                16 => BitRate::Bits16,
+                _ => return None,
+            },
+            channels: match sapi_fmt.nChannels {
+                1 => Channels::Mono,
+                2 => Channels::Stereo,
+                _ => return None,
+            },
+        })
+    }
+
     pub(super) fn to_sapi(&self) -> WAVEFORMATEX {
         let block_align = (self.channels as u32) * (self.bit_rate as u32) / 8;
         WAVEFORMATEX {
@@ -1,7 +1,15 @@
-//! Support for streaming audio to and from files and memory buffers.
+//! Support for streaming audio to and from files and memory buffers, for saving audio as WAV
+//! files, and for enumerating the audio devices on the system.
 
+mod device;
 mod format;
 mod stream;
+mod wav;
 
+pub use device::{
+    default_input_device, default_output_device, input_devices, output_devices, AudioDevice,
+    AudioDirection,
+};
 pub use format::{AudioFormat, BitRate, Channels, SampleRate};
 pub use stream::{AudioStream, MemoryStream};
+pub use wav::{save_wav, write_wav};
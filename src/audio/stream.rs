@@ -78,7 +78,8 @@ impl MemoryStream {
     /// If successful, returns a stream backed by the same memory buffer, but with its own
     /// independent seek pointer.
     pub fn try_clone(&self) -> Result<Self> {
-        unsafe { self.intf.Clone() }.map(|intf| Self { intf: Intf(intf) })
+        let intf = unsafe { self.intf.Clone() }?;
+        Ok(Self { intf: Intf(intf) })
     }
 
     fn create_stream(init_data: Option<&[u8]>) -> std::result::Result<IStream, HRESULT> {
@@ -0,0 +1,112 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::AudioFormat;
+
+/// Writes the given PCM audio data into the writer as a WAV file in the specified format.
+pub fn write_wav<W: Write>(mut writer: W, format: &AudioFormat, data: &[u8]) -> io::Result<()> {
+    let data_len: u32 = data
+        .len()
+        .try_into()
+        .ok()
+        .filter(|len| *len <= u32::MAX - 37)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "audio data too large"))?;
+    // RIFF chunks must have an even length, so an odd-length data chunk is followed by a pad byte
+    let pad_len = data_len & 1;
+    let block_align = (format.channels as u16) * (format.bit_rate as u16) / 8;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(36 + data_len + pad_len).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?;
+    writer.write_all(&(format.channels as u16).to_le_bytes())?;
+    writer.write_all(&(format.sample_rate as u32).to_le_bytes())?;
+    writer.write_all(&((format.sample_rate as u32) * (block_align as u32)).to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&(format.bit_rate as u16).to_le_bytes())?;
+
+    writer.write_all(b"data")?;
+    writer.write_all(&data_len.to_le_bytes())?;
+    writer.write_all(data)?;
+    if pad_len != 0 {
+        writer.write_all(&[0])?;
+    }
+    writer.flush()
+}
+
+/// Creates a WAV file at the given path and writes the given PCM audio data into it.
+pub fn save_wav<P: AsRef<Path>>(path: P, format: &AudioFormat, data: &[u8]) -> io::Result<()> {
+    write_wav(BufWriter::new(File::create(path)?), format, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{BitRate, Channels, SampleRate};
+
+    fn expected_header(riff_len: u32, format: &[u8], data_len: u32) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&riff_len.to_le_bytes());
+        header.extend_from_slice(b"WAVEfmt ");
+        header.extend_from_slice(&16u32.to_le_bytes());
+        header.extend_from_slice(format);
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&data_len.to_le_bytes());
+        header
+    }
+
+    #[test]
+    fn test_write_wav() {
+        let format = AudioFormat {
+            sample_rate: SampleRate::Hz16000,
+            bit_rate: BitRate::Bits16,
+            channels: Channels::Stereo,
+        };
+        let data = [1u8, 2, 3, 4, 5, 6, 7, 8];
+
+        let mut wav = Vec::new();
+        write_wav(&mut wav, &format, &data).unwrap();
+
+        let mut fmt_chunk = Vec::new();
+        fmt_chunk.extend_from_slice(&1u16.to_le_bytes());
+        fmt_chunk.extend_from_slice(&2u16.to_le_bytes());
+        fmt_chunk.extend_from_slice(&16000u32.to_le_bytes());
+        fmt_chunk.extend_from_slice(&64000u32.to_le_bytes());
+        fmt_chunk.extend_from_slice(&4u16.to_le_bytes());
+        fmt_chunk.extend_from_slice(&16u16.to_le_bytes());
+        let mut expected = expected_header(44, &fmt_chunk, 8);
+        expected.extend_from_slice(&data);
+        assert_eq!(expected, wav);
+    }
+
+    #[test]
+    fn test_write_wav_odd_length() {
+        let format = AudioFormat {
+            sample_rate: SampleRate::Hz8000,
+            bit_rate: BitRate::Bits8,
+            channels: Channels::Mono,
+        };
+        let data = [1u8, 2, 3];
+
+        let mut wav = Vec::new();
+        write_wav(&mut wav, &format, &data).unwrap();
+
+        let mut fmt_chunk = Vec::new();
+        fmt_chunk.extend_from_slice(&1u16.to_le_bytes());
+        fmt_chunk.extend_from_slice(&1u16.to_le_bytes());
+        fmt_chunk.extend_from_slice(&8000u32.to_le_bytes());
+        fmt_chunk.extend_from_slice(&8000u32.to_le_bytes());
+        fmt_chunk.extend_from_slice(&1u16.to_le_bytes());
+        fmt_chunk.extend_from_slice(&8u16.to_le_bytes());
+        // The data chunk keeps its length, but the RIFF chunk counts the pad byte
+        let mut expected = expected_header(40, &fmt_chunk, 3);
+        expected.extend_from_slice(&data);
+        expected.push(0);
+        assert_eq!(expected, wav);
+    }
+}
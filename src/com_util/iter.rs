@@ -7,7 +7,7 @@ use crate::Result;
 
 pub unsafe fn next_elem<I, R>(
     intf: &I,
-    f: unsafe fn(&I, u32, *mut R, *mut u32) -> Result<()>,
+    f: unsafe fn(&I, u32, *mut R, *mut u32) -> windows::core::Result<()>,
 ) -> Result<Option<R>> {
     let mut result = MaybeUninit::uninit();
     let mut fetched = MaybeUninit::uninit();
@@ -21,7 +21,7 @@ pub unsafe fn next_elem<I, R>(
 
 pub unsafe fn next_obj<I: Interface, R: Interface>(
     intf: &I,
-    f: unsafe fn(&I, u32, *mut Option<R>, *mut u32) -> Result<()>,
+    f: unsafe fn(&I, u32, *mut Option<R>, *mut u32) -> windows::core::Result<()>,
 ) -> Result<Option<R>> {
     let mut result = MaybeUninit::uninit();
     let mut fetched = MaybeUninit::uninit();
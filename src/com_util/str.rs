@@ -11,6 +11,14 @@ pub unsafe fn from_wide(s: &PWSTR) -> OsString {
     OsString::from_wide(slice)
 }
 
+pub unsafe fn opt_from_wide(s: &PWSTR) -> Option<OsString> {
+    if s.is_null() {
+        None
+    } else {
+        Some(from_wide(s))
+    }
+}
+
 pub fn opt_str_param<'p, S: AsRef<str>>(opt: Option<S>) -> Param<'p, PWSTR> {
     match opt {
         Some(s) => s.as_ref().into_param(),
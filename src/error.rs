@@ -0,0 +1,143 @@
+use std::fmt::{self, Display};
+
+use windows as Windows;
+use Windows::core::HRESULT;
+use Windows::Win32::Foundation::{CO_E_NOTINITIALIZED, REGDB_E_CLASSNOTREG};
+
+// SPERR_ constants are absent from the windows crate
+pub(crate) const SPERR_UNSUPPORTED_FORMAT: HRESULT = HRESULT(0x80045003);
+pub(crate) const SPERR_DEVICE_BUSY: HRESULT = HRESULT(0x80045006);
+pub(crate) const SPERR_NOT_FOUND: HRESULT = HRESULT(0x8004503A);
+pub(crate) const SPERR_STATE_WITH_NO_ARCS: HRESULT = HRESULT(0x80045062);
+
+/// The error type returned by SAPI functions and methods.
+///
+/// The common failures are represented by dedicated variants. Every variant holds the underlying
+/// COM error, and its `HRESULT` is available through [`hresult`](Error::hresult).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// SAPI has not been initialized on the current thread. See [`initialize`](crate::initialize).
+    NotInitialized(windows::core::Error),
+    /// The requested engine, voice, device, or other component is not installed on the system.
+    NotInstalled(windows::core::Error),
+    /// The grammar could not be built.
+    Grammar {
+        /// The name of the top-level rule that could not be built, if it's known and named.
+        rule: Option<String>,
+        /// The underlying COM error.
+        source: windows::core::Error,
+    },
+    /// The audio format is not supported.
+    UnsupportedFormat(windows::core::Error),
+    /// The audio device is being used by another application.
+    DeviceBusy(windows::core::Error),
+    /// Any other failure.
+    Other(windows::core::Error),
+}
+
+impl Error {
+    /// Returns the `HRESULT` of the underlying COM error.
+    pub fn hresult(&self) -> HRESULT {
+        self.com_error().code()
+    }
+
+    /// Returns the underlying COM error.
+    pub fn com_error(&self) -> &windows::core::Error {
+        match self {
+            Self::NotInitialized(err) => err,
+            Self::NotInstalled(err) => err,
+            Self::Grammar { source, .. } => source,
+            Self::UnsupportedFormat(err) => err,
+            Self::DeviceBusy(err) => err,
+            Self::Other(err) => err,
+        }
+    }
+
+    // Attributes a grammar error to the given rule, leaving the other failures as they are
+    pub(crate) fn in_rule(self, rule: Option<&str>) -> Self {
+        match self {
+            Self::Grammar { rule: None, source } => Self::Grammar {
+                rule: rule.map(String::from),
+                source,
+            },
+            err => err,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInitialized(_) => write!(f, "SAPI is not initialized on this thread")?,
+            Self::NotInstalled(_) => write!(f, "the requested component is not installed")?,
+            Self::Grammar {
+                rule: Some(rule), ..
+            } => write!(f, "failed to build the grammar rule {:?}", rule)?,
+            Self::Grammar { rule: None, .. } => write!(f, "failed to build the grammar")?,
+            Self::UnsupportedFormat(_) => write!(f, "the audio format is not supported")?,
+            Self::DeviceBusy(_) => write!(f, "the audio device is busy")?,
+            Self::Other(_) => write!(f, "SAPI call failed")?,
+        }
+        write!(f, " ({:#010X}): {}", self.hresult().0, self.com_error())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.com_error())
+    }
+}
+
+impl From<windows::core::Error> for Error {
+    fn from(err: windows::core::Error) -> Self {
+        match err.code() {
+            CO_E_NOTINITIALIZED => Self::NotInitialized(err),
+            SPERR_NOT_FOUND | REGDB_E_CLASSNOTREG => Self::NotInstalled(err),
+            SPERR_STATE_WITH_NO_ARCS => Self::Grammar {
+                rule: None,
+                source: err,
+            },
+            SPERR_UNSUPPORTED_FORMAT => Self::UnsupportedFormat(err),
+            SPERR_DEVICE_BUSY => Self::DeviceBusy(err),
+            _ => Self::Other(err),
+        }
+    }
+}
+
+impl From<Error> for windows::core::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::NotInitialized(err) => err,
+            Error::NotInstalled(err) => err,
+            Error::Grammar { source, .. } => source,
+            Error::UnsupportedFormat(err) => err,
+            Error::DeviceBusy(err) => err,
+            Error::Other(err) => err,
+        }
+    }
+}
+
+impl From<HRESULT> for Error {
+    fn from(code: HRESULT) -> Self {
+        windows::core::Error::from(code).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::Foundation::E_OUTOFMEMORY;
+
+    use super::*;
+
+    #[test]
+    fn test_in_rule() {
+        match Error::from(SPERR_STATE_WITH_NO_ARCS).in_rule(Some("empty")) {
+            Error::Grammar { rule, .. } => assert_eq!(Some("empty".to_string()), rule),
+            err => panic!("unexpected error: {:?}", err),
+        }
+        assert!(matches!(
+            Error::from(E_OUTOFMEMORY).in_rule(Some("empty")),
+            Error::Other(_)
+        ));
+    }
+}
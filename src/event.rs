@@ -3,9 +3,11 @@ use Windows::core::{implement, IUnknown};
 use Windows::Win32::Foundation::PWSTR;
 use Windows::Win32::Media::Speech::{
     ISpEventSource, ISpNotifySink, ISpObjectToken, ISpRecoResult, SPEI_END_INPUT_STREAM,
-    SPEI_RECOGNITION, SPEI_RESERVED1, SPEI_RESERVED2, SPET_LPARAM_IS_OBJECT,
-    SPET_LPARAM_IS_POINTER, SPET_LPARAM_IS_STRING, SPET_LPARAM_IS_TOKEN, SPET_LPARAM_IS_UNDEFINED,
-    SPEVENT, SPEVENTENUM, SPEVENTLPARAMTYPE,
+    SPEI_FALSE_RECOGNITION, SPEI_HYPOTHESIS, SPEI_INTERFERENCE, SPEI_PHRASE_START,
+    SPEI_RECOGNITION, SPEI_RESERVED1, SPEI_RESERVED2, SPEI_SOUND_END, SPEI_SOUND_START,
+    SPEI_SR_AUDIO_LEVEL, SPET_LPARAM_IS_OBJECT, SPET_LPARAM_IS_POINTER, SPET_LPARAM_IS_STRING,
+    SPET_LPARAM_IS_TOKEN, SPET_LPARAM_IS_UNDEFINED, SPEVENT, SPEVENTENUM, SPEVENTLPARAMTYPE,
+    SPINTERFERENCE,
 };
 
 use crate::com_util::{next_elem, ComBox, MaybeWeak};
@@ -15,6 +17,13 @@ use crate::Result;
 #[derive(Debug)]
 pub(crate) enum Event {
     Recognition(ISpRecoResult),
+    Hypothesis(ISpRecoResult),
+    FalseRecognition(ISpRecoResult),
+    Interference(SPINTERFERENCE),
+    SoundStart(u64),
+    SoundEnd(u64),
+    PhraseStart(u64),
+    AudioLevel(u32, u64),
     SpeechFinished(u32),
     OtherObject(IUnknown),
     OtherToken(Token),
@@ -34,6 +43,8 @@ impl Event {
                 let intf = unsafe { IUnknown::from_abi(lparam as _) }?;
                 match id {
                     SPEI_RECOGNITION => Ok(Self::Recognition(intf.cast()?)),
+                    SPEI_HYPOTHESIS => Ok(Self::Hypothesis(intf.cast()?)),
+                    SPEI_FALSE_RECOGNITION => Ok(Self::FalseRecognition(intf.cast()?)),
                     _ => Ok(Self::OtherObject(intf)),
                 }
             }
@@ -48,6 +59,14 @@ impl Event {
             }?))),
             SPET_LPARAM_IS_UNDEFINED => match id {
                 SPEI_END_INPUT_STREAM => Ok(Self::SpeechFinished(sapi_event.ulStreamNum)),
+                SPEI_INTERFERENCE => Ok(Self::Interference(SPINTERFERENCE(lparam as _))),
+                SPEI_SOUND_START => Ok(Self::SoundStart(sapi_event.ullAudioStreamOffset)),
+                SPEI_SOUND_END => Ok(Self::SoundEnd(sapi_event.ullAudioStreamOffset)),
+                SPEI_PHRASE_START => Ok(Self::PhraseStart(sapi_event.ullAudioStreamOffset)),
+                SPEI_SR_AUDIO_LEVEL => Ok(Self::AudioLevel(
+                    sapi_event.wParam.0 as _,
+                    sapi_event.ullAudioStreamOffset,
+                )),
                 _ => Ok(Self::Other),
             },
             _ => panic!("Unrecognized SPEVENTLPARAMTYPE value"),
@@ -106,20 +125,25 @@ impl EventSink {
         unsafe { src_intf.SetNotifySink(&sink_intf) }?;
         unsafe { Self::to_impl(&sink_intf) }.source.downgrade();
 
-        if let Some(flags) = interest {
-            let mut flags_arg = (1u64 << SPEI_RESERVED1.0) | (1u64 << SPEI_RESERVED2.0);
-            for flag in flags {
-                flags_arg |= 1u64 << flag.0;
-            }
-            unsafe { src_intf.SetInterest(flags_arg, flags_arg) }?;
+        if let Some(events) = interest {
+            set_interest(&src_intf, events)?;
         }
         Ok(())
     }
 
-    fn Notify(&self) -> Result<()> {
+    fn Notify(&self) -> windows::core::Result<()> {
         while let Some(event) = self.source.next_event()? {
             (*self.handler)(event)?
         }
         Ok(())
     }
 }
+
+pub(crate) fn set_interest(intf: &ISpEventSource, events: &[SPEVENTENUM]) -> Result<()> {
+    let mut flags = (1u64 << SPEI_RESERVED1.0) | (1u64 << SPEI_RESERVED2.0);
+    for event in events {
+        flags |= 1u64 << event.0;
+    }
+    unsafe { intf.SetInterest(flags, flags) }?;
+    Ok(())
+}
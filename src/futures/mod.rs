@@ -0,0 +1,16 @@
+//! Support for async operations that don't depend on a particular runtime.
+//!
+//! The types in this module are built on the primitives of the [`futures`](::futures) crate, so
+//! they can be used with Tokio, async-std, smol, or any other executor. The subscribers implement
+//! [`Stream`](::futures::Stream), so they compose with the combinators from
+//! [`StreamExt`](::futures::StreamExt) and with `select!`.
+
+#[cfg(feature = "futures-stt")]
+mod stt;
+
+#[cfg(feature = "futures-tts")]
+pub use crate::tts::asynchronous::AsyncSynthesizer;
+#[cfg(feature = "futures-stt")]
+pub use stt::{
+    BroadcastContext, BroadcastSubscriber, HypothesisSubscriber, UnicastContext, UnicastSubscriber,
+};
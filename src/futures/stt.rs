@@ -0,0 +1,344 @@
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context as TaskContext, Poll};
+
+use futures::channel::mpsc;
+use futures::{Stream, StreamExt};
+use windows as Windows;
+use Windows::Win32::Foundation::E_INVALIDARG;
+
+use crate::stt::{
+    Context, EventHandler, EventfulContext, Intents, Interference, Phrase, Recognition,
+    RecognizedIntent, RecognizerBase,
+};
+use crate::Result;
+
+/// A subscriber that can be awaited for recognized phrases. As a [`Stream`], it yields the outcome
+/// of every attempt to recognize a phrase, and ends when its context is closed or dropped.
+#[cfg_attr(docsrs, doc(cfg(feature = "futures-stt")))]
+pub struct UnicastSubscriber {
+    rx: mpsc::Receiver<Recognition>,
+}
+
+impl UnicastSubscriber {
+    /// Completes when the engine recognizes a phrase, or with `None` if the context has been
+    /// closed or dropped. Phrases rejected for having less than the minimum confidence are
+    /// skipped.
+    pub async fn recognize(&mut self) -> Option<Phrase> {
+        next_accepted(self).await
+    }
+
+    /// Completes when the engine recognizes a phrase that can be decoded into one of the given
+    /// intents, or with `None` if the context has been closed or dropped. Phrases that cannot be
+    /// decoded are skipped, as are phrases rejected for having less than the minimum confidence.
+    pub async fn recognize_intent<T>(
+        &mut self,
+        intents: &Intents<T>,
+    ) -> Option<RecognizedIntent<T>> {
+        loop {
+            if let Some(intent) = intents.decode(self.recognize().await?) {
+                return Some(intent);
+            }
+        }
+    }
+
+    /// Completes when the engine concludes an attempt to recognize a phrase, or with `None` if the
+    /// context has been closed or dropped. Unlike [`recognize`](Self::recognize), this also yields
+    /// rejected phrases, false recognitions, and interference with the audio. False recognitions
+    /// and interference must be enabled with
+    /// [`Context::set_interest`](crate::stt::Context::set_interest).
+    pub async fn recognize_outcome(&mut self) -> Option<Recognition> {
+        self.next().await
+    }
+}
+
+impl Stream for UnicastSubscriber {
+    type Item = Recognition;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_next_unpin(cx)
+    }
+}
+
+/// One of several subscribers that can be awaited for recognized phrases. As a [`Stream`], it
+/// yields the outcome of every attempt to recognize a phrase, and ends when its context is closed
+/// or dropped.
+///
+/// Every subscriber has its own buffer. If a subscriber lags too far behind, the outcomes that
+/// don't fit into its buffer are skipped, and counted in [`skipped`](Self::skipped).
+#[cfg_attr(docsrs, doc(cfg(feature = "futures-stt")))]
+pub struct BroadcastSubscriber {
+    rx: mpsc::Receiver<Recognition>,
+    skipped: Arc<AtomicU64>,
+}
+
+impl BroadcastSubscriber {
+    /// Completes when the engine recognizes a phrase, or with `None` if the context has been
+    /// closed or dropped. Phrases rejected for having less than the minimum confidence are
+    /// skipped.
+    pub async fn recognize(&mut self) -> Option<Phrase> {
+        next_accepted(self).await
+    }
+
+    /// Returns how many outcomes this subscriber has skipped because it lagged too far behind.
+    pub fn skipped(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+}
+
+impl Stream for BroadcastSubscriber {
+    type Item = Recognition;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_next_unpin(cx)
+    }
+}
+
+/// A subscriber that yields hypotheses, i.e. partial phrases the engine reports while the user is
+/// speaking. Hypotheses must be enabled with
+/// [`Context::set_interest`](crate::stt::Context::set_interest).
+///
+/// If the subscriber lags too far behind, the hypotheses that don't fit into its buffer are
+/// skipped, and counted in [`skipped`](Self::skipped).
+#[cfg_attr(docsrs, doc(cfg(feature = "futures-stt")))]
+pub struct HypothesisSubscriber {
+    rx: mpsc::Receiver<Phrase>,
+    skipped: Arc<AtomicU64>,
+}
+
+impl HypothesisSubscriber {
+    /// Returns how many hypotheses this subscriber has skipped because it lagged too far behind.
+    pub fn skipped(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+}
+
+impl Stream for HypothesisSubscriber {
+    type Item = Phrase;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_next_unpin(cx)
+    }
+}
+
+/// A recognition context paired with a single subscriber that can be awaited for recognition.
+#[cfg_attr(docsrs, doc(cfg(feature = "futures-stt")))]
+pub struct UnicastContext {
+    base: EventfulContext,
+    channels: Arc<Channels>,
+}
+
+impl UnicastContext {
+    /// Creates a new recognition context for the given recognizer, configured to buffer up to the
+    /// given number of recognized phrases. If a new phrase is recognized while the buffer is full,
+    /// it will be silently dropped. The buffer must be at least 1.
+    pub fn new(recognizer: &RecognizerBase, buffer: usize) -> Result<(Self, UnicastSubscriber)> {
+        let channels = Arc::new(Channels::new(buffer)?);
+        let (rx, _) = channels.recognitions.subscribe();
+        Ok((
+            Self {
+                base: EventfulContext::new(recognizer, ChannelHandler(channels.clone()))?,
+                channels,
+            },
+            UnicastSubscriber { rx },
+        ))
+    }
+
+    /// Creates a subscriber for the hypotheses formed in this context.
+    pub fn hypotheses(&self) -> HypothesisSubscriber {
+        let (rx, skipped) = self.channels.hypotheses.subscribe();
+        HypothesisSubscriber { rx, skipped }
+    }
+
+    /// Closes the context, so that its subscribers stop waiting. The phrases recognized afterwards
+    /// are dropped.
+    pub fn close(&self) {
+        self.channels.close();
+    }
+}
+
+impl Deref for UnicastContext {
+    type Target = Context;
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl Drop for UnicastContext {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// A recognition context paired with a one or more subscribers that can be awaited for recognition.
+#[cfg_attr(docsrs, doc(cfg(feature = "futures-stt")))]
+pub struct BroadcastContext {
+    base: EventfulContext,
+    channels: Arc<Channels>,
+}
+
+impl BroadcastContext {
+    /// Creates a new recognition context for the given recognizer, configured to buffer up to the
+    /// given number of recognized phrases for each subscriber. The buffer must be at least 1.
+    pub fn new(recognizer: &RecognizerBase, buffer: usize) -> Result<(Self, BroadcastSubscriber)> {
+        let channels = Arc::new(Channels::new(buffer)?);
+        let ctx = Self {
+            base: EventfulContext::new(recognizer, ChannelHandler(channels.clone()))?,
+            channels,
+        };
+        let subscriber = ctx.subscribe();
+        Ok((ctx, subscriber))
+    }
+
+    /// Creates a subscriber for this context.
+    pub fn subscribe(&self) -> BroadcastSubscriber {
+        let (rx, skipped) = self.channels.recognitions.subscribe();
+        BroadcastSubscriber { rx, skipped }
+    }
+
+    /// Creates a subscriber for the hypotheses formed in this context.
+    pub fn hypotheses(&self) -> HypothesisSubscriber {
+        let (rx, skipped) = self.channels.hypotheses.subscribe();
+        HypothesisSubscriber { rx, skipped }
+    }
+
+    /// Closes the context, so that its subscribers stop waiting. The phrases recognized afterwards
+    /// are dropped.
+    pub fn close(&self) {
+        self.channels.close();
+    }
+}
+
+impl Deref for BroadcastContext {
+    type Target = Context;
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl Drop for BroadcastContext {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+async fn next_accepted<S: Stream<Item = Recognition> + Unpin>(stream: &mut S) -> Option<Phrase> {
+    loop {
+        if let Recognition::Accepted(phrase) = stream.next().await? {
+            return Some(phrase);
+        }
+    }
+}
+
+// The event handler is owned by SAPI, which may outlive the context, so the context closes the
+// channels explicitly to end the subscriber streams
+struct Channels {
+    recognitions: Broadcaster<Recognition>,
+    hypotheses: Broadcaster<Phrase>,
+}
+
+impl Channels {
+    fn new(buffer: usize) -> Result<Self> {
+        // A channel without capacity would skip every item the subscriber isn't already waiting
+        // for
+        if buffer == 0 {
+            return Err(E_INVALIDARG.into());
+        }
+        Ok(Self {
+            recognitions: Broadcaster::new(buffer),
+            hypotheses: Broadcaster::new(buffer),
+        })
+    }
+
+    fn close(&self) {
+        self.recognitions.close();
+        self.hypotheses.close();
+    }
+}
+
+// Futures don't offer a broadcast channel, so every subscriber gets a channel of its own
+struct Broadcaster<T> {
+    buffer: usize,
+    // None once the broadcaster is closed
+    subscribers: Mutex<Option<Vec<Subscription<T>>>>,
+}
+
+// The sender for the subscriber, with the number of items the subscriber has skipped
+type Subscription<T> = (mpsc::Sender<T>, Arc<AtomicU64>);
+
+impl<T: Clone> Broadcaster<T> {
+    fn new(buffer: usize) -> Self {
+        Self {
+            buffer,
+            subscribers: Mutex::new(Some(Vec::new())),
+        }
+    }
+
+    fn subscribe(&self) -> (mpsc::Receiver<T>, Arc<AtomicU64>) {
+        // Every sender gets a slot of its own on top of the buffer, so the buffer is one smaller
+        let (tx, rx) = mpsc::channel(self.buffer - 1);
+        let skipped = Arc::new(AtomicU64::new(0));
+        // Dropping the sender of a closed broadcaster ends the stream right away
+        if let Some(subscribers) = self.subscribers.lock().unwrap().as_mut() {
+            subscribers.push((tx, skipped.clone()));
+        }
+        (rx, skipped)
+    }
+
+    fn send(&self, item: T) {
+        let mut guard = self.subscribers.lock().unwrap();
+        let subscribers = match guard.as_mut() {
+            Some(subscribers) => subscribers,
+            None => return,
+        };
+        let mut idx = 0;
+        while idx < subscribers.len() {
+            let (tx, skipped) = &mut subscribers[idx];
+            match tx.try_send(item.clone()) {
+                Err(err) if err.is_disconnected() => {
+                    subscribers.swap_remove(idx);
+                    continue;
+                }
+                Err(_) => {
+                    skipped.fetch_add(1, Ordering::Relaxed);
+                }
+                Ok(()) => (),
+            }
+            idx += 1;
+        }
+    }
+
+    fn close(&self) {
+        *self.subscribers.lock().unwrap() = None;
+    }
+}
+
+struct ChannelHandler(Arc<Channels>);
+
+impl EventHandler for ChannelHandler {
+    fn on_recognition(&self, phrase: Phrase) {
+        self.0.recognitions.send(Recognition::Accepted(phrase))
+    }
+
+    fn on_rejection(&self, phrase: Phrase) {
+        self.0.recognitions.send(Recognition::Rejected(phrase))
+    }
+
+    fn on_false_recognition(&self, phrase: Option<Phrase>) {
+        self.0
+            .recognitions
+            .send(Recognition::FalseRecognition(phrase))
+    }
+
+    fn on_interference(&self, interference: Interference) {
+        self.0
+            .recognitions
+            .send(Recognition::Interference(interference))
+    }
+
+    fn on_hypothesis(&self, phrase: Phrase) {
+        self.0.hypotheses.send(phrase)
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt::{self, Display};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{LexiconWord, PartOfSpeech};
+
+const SEPARATOR: char = '|';
+const COMMENT: char = '#';
+
+/// The reason a lexicon file could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseLexiconErrorKind {
+    /// The line doesn't have exactly four fields; holds the number of fields it has.
+    WrongFieldCount(usize),
+    /// The field with the given name is empty.
+    EmptyField(&'static str),
+    /// The part of speech is not recognized; holds the unrecognized value.
+    UnknownPartOfSpeech(String),
+}
+
+/// An error in a lexicon file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLexiconError {
+    /// The number of the offending line, starting from 1.
+    pub line: usize,
+    /// What is wrong with the line.
+    pub kind: ParseLexiconErrorKind,
+}
+
+impl Display for ParseLexiconError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ParseLexiconErrorKind::WrongFieldCount(count) => {
+                write!(f, "expected 4 fields, found {}", count)
+            }
+            ParseLexiconErrorKind::EmptyField(name) => write!(f, "the {} is empty", name),
+            ParseLexiconErrorKind::UnknownPartOfSpeech(value) => {
+                write!(f, "unknown part of speech {:?}", value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseLexiconError {}
+
+/// Parses the words in the lexicon file format.
+///
+/// Every non-empty line that doesn't start with `#` describes one word with four fields separated
+/// by `|`: the text, the language, the part of speech, and the pronunciation. For example:
+/// ```
+/// # use sapi_lite::lexicon::{parse_lexicon, PartOfSpeech};
+/// let words = parse_lexicon("
+///     # text | language | part of speech | pronunciation
+///     Zyxel  | en-US    | noun           | z ay 1 k s eh l
+/// ").unwrap();
+/// assert_eq!(PartOfSpeech::Noun, words[0].part_of_speech);
+/// ```
+pub fn parse_lexicon(text: &str) -> Result<Vec<LexiconWord>, ParseLexiconError> {
+    let mut words = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(COMMENT) {
+            continue;
+        }
+        let error = |kind| ParseLexiconError {
+            line: idx + 1,
+            kind,
+        };
+
+        let fields: Vec<&str> = line.split(SEPARATOR).map(str::trim).collect();
+        if fields.len() != 4 {
+            return Err(error(ParseLexiconErrorKind::WrongFieldCount(fields.len())));
+        }
+        for (field, name) in
+            fields
+                .iter()
+                .zip(["text", "language", "part of speech", "pronunciation"])
+        {
+            if field.is_empty() {
+                return Err(error(ParseLexiconErrorKind::EmptyField(name)));
+            }
+        }
+        let part_of_speech = PartOfSpeech::from_str(fields[2]).map_err(|_| {
+            error(ParseLexiconErrorKind::UnknownPartOfSpeech(
+                fields[2].to_string(),
+            ))
+        })?;
+
+        words.push(LexiconWord::new(
+            fields[0],
+            fields[1],
+            part_of_speech,
+            fields[3],
+        ));
+    }
+    Ok(words)
+}
+
+/// Reads the words from the lexicon file at the given path. See [`parse_lexicon`] for the format.
+pub fn load_lexicon<P: AsRef<Path>>(path: P) -> io::Result<Vec<LexiconWord>> {
+    parse_lexicon(&fs::read_to_string(path)?)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Writes the given words into the writer in the lexicon file format. See [`parse_lexicon`] for
+/// the format.
+pub fn write_lexicon<W: Write>(mut writer: W, words: &[LexiconWord]) -> io::Result<()> {
+    for word in words {
+        let fields = [
+            word.text.as_str(),
+            word.language.as_str(),
+            word.part_of_speech.into(),
+            word.pronunciation.as_str(),
+        ];
+        if fields.iter().any(|field| !is_valid_field(field)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("word {:?} cannot be written to a lexicon file", word.text),
+            ));
+        }
+        writeln!(writer, "{}", fields.join(" | "))?;
+    }
+    writer.flush()
+}
+
+/// Creates a lexicon file at the given path and writes the given words into it.
+pub fn save_lexicon<P: AsRef<Path>>(path: P, words: &[LexiconWord]) -> io::Result<()> {
+    write_lexicon(BufWriter::new(File::create(path)?), words)
+}
+
+fn is_valid_field(field: &str) -> bool {
+    !field.is_empty()
+        && field.trim() == field
+        && !field.starts_with(COMMENT)
+        && !field.contains(|c| c == SEPARATOR || c == '\n' || c == '\r')
+}
@@ -0,0 +1,200 @@
+//! User lexicon management.
+//!
+//! The engines consult the user lexicon to find out how words are pronounced, both when they
+//! synthesize speech and when they recognize it. Adding a word to the [`Lexicon`] therefore
+//! teaches the pronunciation to the synthesizers and the recognizers alike.
+//!
+//! Lists of [`LexiconWord`]s can be stored in a simple text format, e.g. to keep the custom
+//! vocabulary under version control. Reading and writing the format doesn't involve SAPI. See
+//! [`parse_lexicon`] for the details of the format.
+
+use std::ffi::c_void;
+use std::mem::zeroed;
+
+use windows as Windows;
+use Windows::Win32::Foundation::{E_INVALIDARG, PWSTR};
+use Windows::Win32::Media::Speech::{
+    eLEXTYPE_APP, eLEXTYPE_USER, eWORDTYPE_ADDED, ISpLexicon, ISpPhoneConverter, SPPS_Function,
+    SPPS_Interjection, SPPS_Modifier, SPPS_Noun, SPPS_Unknown, SPPS_Verb, SpLexicon,
+    SPPARTOFSPEECH, SPWORDLIST, SP_MAX_PRON_LENGTH,
+};
+use Windows::Win32::System::Com::{CoCreateInstance, CoTaskMemFree, CLSCTX_ALL};
+
+use crate::com_util::{from_wide, Intf, Locale};
+use crate::error::SPERR_NOT_FOUND;
+use crate::token::{Category, TokenSelector};
+use crate::Result;
+
+mod file;
+mod word;
+
+// The longest phone string a phone converter produces, as defined by `g_dwMaxLenPhone` in
+// sphelper.h
+const MAX_PHONE_LENGTH: usize = 7;
+
+pub use file::{
+    load_lexicon, parse_lexicon, save_lexicon, write_lexicon, ParseLexiconError,
+    ParseLexiconErrorKind,
+};
+pub use word::{LexiconWord, PartOfSpeech};
+
+/// The lexicon of the current user, shared by all the speech engines.
+pub struct Lexicon {
+    intf: Intf<ISpLexicon>,
+}
+
+impl Lexicon {
+    /// Opens the lexicon of the current user.
+    pub fn new() -> Result<Self> {
+        let intf = unsafe { CoCreateInstance(&SpLexicon, None, CLSCTX_ALL) }?;
+        Ok(Self { intf: Intf(intf) })
+    }
+
+    /// Adds the given word with its pronunciation to the lexicon.
+    pub fn add(&self, word: &LexiconWord) -> Result<()> {
+        let (lang_id, phone_ids) = phone_ids(word)?;
+        unsafe {
+            self.intf.AddPronunciation(
+                word.text.as_str(),
+                lang_id,
+                part_of_speech_to_sapi(word.part_of_speech),
+                phone_ids.as_ptr(),
+            )
+        }?;
+        Ok(())
+    }
+
+    /// Adds all the given words to the lexicon.
+    pub fn import<'w, I: IntoIterator<Item = &'w LexiconWord>>(&self, words: I) -> Result<()> {
+        for word in words {
+            self.add(word)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the given pronunciation of the word from the lexicon.
+    pub fn remove(&self, word: &LexiconWord) -> Result<()> {
+        let (lang_id, phone_ids) = phone_ids(word)?;
+        unsafe {
+            self.intf.RemovePronunciation(
+                word.text.as_str(),
+                lang_id,
+                part_of_speech_to_sapi(word.part_of_speech),
+                phone_ids.as_ptr(),
+            )
+        }?;
+        Ok(())
+    }
+
+    /// Returns all the words that have been added to the lexicon, one entry per pronunciation.
+    pub fn words(&self) -> Result<Vec<LexiconWord>> {
+        let mut words = Vec::new();
+        let mut list: SPWORDLIST = unsafe { zeroed() };
+        let mut generation = 0;
+        let mut cookie = 0;
+        let result = loop {
+            let result = unsafe {
+                self.intf.GetWords(
+                    (eLEXTYPE_USER.0 | eLEXTYPE_APP.0) as _,
+                    &mut generation,
+                    &mut cookie,
+                    &mut list,
+                )
+            };
+            if let Err(err) = result {
+                break Err(err.into());
+            }
+            if list.pFirstWord.is_null() {
+                break Ok(());
+            }
+            if let Err(err) = unsafe { collect_words(&list, &mut words) } {
+                break Err(err);
+            }
+            if cookie == 0 {
+                break Ok(());
+            }
+        };
+        // The buffer is reused across the calls, and must be freed even if one of them fails
+        unsafe { CoTaskMemFree(list.pvBuffer as *const c_void) };
+        result?;
+        Ok(words)
+    }
+}
+
+unsafe fn collect_words(list: &SPWORDLIST, words: &mut Vec<LexiconWord>) -> Result<()> {
+    let mut word = list.pFirstWord;
+    while let Some(sapi_word) = word.as_ref() {
+        if sapi_word.eWordType == eWORDTYPE_ADDED {
+            let text = from_wide(&sapi_word.pszWord).to_string_lossy().into_owned();
+            let mut pron = sapi_word.pFirstWordPronunciation;
+            while let Some(sapi_pron) = pron.as_ref() {
+                words.push(LexiconWord::new(
+                    text.clone(),
+                    Locale::new(sapi_pron.LangID as _)
+                        .name()
+                        .to_string_lossy()
+                        .into_owned(),
+                    part_of_speech_from_sapi(sapi_pron.ePartOfSpeech),
+                    phones(sapi_pron.LangID, sapi_pron.szPronunciation.as_ptr())?,
+                ));
+                pron = sapi_pron.pNextWordPronunciation;
+            }
+        }
+        word = sapi_word.pNextWord;
+    }
+    Ok(())
+}
+
+fn phone_ids(word: &LexiconWord) -> Result<(u16, Vec<u16>)> {
+    let lang_id = word.language.parse::<Locale>()?.lcid() as u16;
+    if word.pronunciation.trim().is_empty() {
+        return Err(E_INVALIDARG.into());
+    }
+    // Every phone is converted to at least one ID, and the list is terminated with a zero
+    let mut ids = vec![0u16; word.pronunciation.len() + 1];
+    unsafe { phone_converter(lang_id)?.PhoneToId(word.pronunciation.as_str(), ids.as_mut_ptr()) }?;
+    Ok((lang_id, ids))
+}
+
+unsafe fn phones(lang_id: u16, ids: *const u16) -> Result<String> {
+    // The converter doesn't take the size of the buffer, so it has to fit the longest possible
+    // pronunciation, where every phone is followed by a space, plus the terminating zero
+    let mut buffer = vec![0u16; SP_MAX_PRON_LENGTH as usize * (MAX_PHONE_LENGTH + 1) + 1];
+    phone_converter(lang_id)?.IdToPhone(ids, PWSTR(buffer.as_mut_ptr()))?;
+    Ok(from_wide(&PWSTR(buffer.as_mut_ptr()))
+        .to_string_lossy()
+        .trim()
+        .to_string())
+}
+
+fn phone_converter(lang_id: u16) -> Result<ISpPhoneConverter> {
+    let category = Category::new(r"HKEY_LOCAL_MACHINE\SOFTWARE\Microsoft\Speech\PhoneConverters")?;
+    let selector = TokenSelector::new().append_condition("language=", &format!("{:X}", lang_id));
+    let token = category
+        .enum_tokens(Some(selector.into_sapi_expr()), None)?
+        .next()
+        .ok_or(SPERR_NOT_FOUND)?;
+    token.create_instance()
+}
+
+fn part_of_speech_to_sapi(part_of_speech: PartOfSpeech) -> SPPARTOFSPEECH {
+    match part_of_speech {
+        PartOfSpeech::Unknown => SPPS_Unknown,
+        PartOfSpeech::Noun => SPPS_Noun,
+        PartOfSpeech::Verb => SPPS_Verb,
+        PartOfSpeech::Modifier => SPPS_Modifier,
+        PartOfSpeech::Function => SPPS_Function,
+        PartOfSpeech::Interjection => SPPS_Interjection,
+    }
+}
+
+fn part_of_speech_from_sapi(part_of_speech: SPPARTOFSPEECH) -> PartOfSpeech {
+    match part_of_speech {
+        SPPS_Noun => PartOfSpeech::Noun,
+        SPPS_Verb => PartOfSpeech::Verb,
+        SPPS_Modifier => PartOfSpeech::Modifier,
+        SPPS_Function => PartOfSpeech::Function,
+        SPPS_Interjection => PartOfSpeech::Interjection,
+        _ => PartOfSpeech::Unknown,
+    }
+}
@@ -0,0 +1,47 @@
+use strum_macros::{EnumString, IntoStaticStr};
+
+/// Specifies the part of speech of a word in the lexicon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumString, IntoStaticStr)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+#[allow(missing_docs)]
+pub enum PartOfSpeech {
+    Unknown,
+    Noun,
+    Verb,
+    Modifier,
+    Function,
+    Interjection,
+}
+
+/// A word in the lexicon, together with one of its pronunciations.
+///
+/// A word with several pronunciations is represented by several entries with the same text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LexiconWord {
+    /// The written form of the word.
+    pub text: String,
+    /// The name of the language of the word, e.g. `"en-US"`.
+    pub language: String,
+    /// The part of speech of the word.
+    pub part_of_speech: PartOfSpeech,
+    /// The pronunciation of the word, as a sequence of phones from the phone set of the language,
+    /// separated by spaces, e.g. `"h eh 1 l ow"`.
+    pub pronunciation: String,
+}
+
+impl LexiconWord {
+    /// Creates a new word with the given text, language, part of speech, and pronunciation.
+    pub fn new<T: Into<String>, L: Into<String>, P: Into<String>>(
+        text: T,
+        language: L,
+        part_of_speech: PartOfSpeech,
+        pronunciation: P,
+    ) -> Self {
+        Self {
+            text: text.into(),
+            language: language.into(),
+            part_of_speech,
+            pronunciation: pronunciation.into(),
+        }
+    }
+}
@@ -47,6 +47,17 @@
 //! in it. Which context type you choose will depend on whether you want to block the execution
 //! while waiting for a phrase to be recognized or not.
 //!
+//! ## Lexicon
+//!
+//! The [lexicon] module lets you teach the engines how to pronounce custom words, such as product
+//! names or surnames, and keep the list of those words in a text file.
+//!
+//! ## Testing
+//!
+//! With the `test-support` feature enabled, the [testing](crate::testing) module provides a
+//! harness for checking which utterances a grammar accepts or rejects, and which semantic
+//! information it produces for them.
+//!
 //! # COM and Lifetime of SAPI Types
 //!
 //! Microsoft SAPI is a COM library. All COM objects and interfaces use reference counting to
@@ -68,24 +79,31 @@ use windows::Win32::System::Com::{CoInitialize, CoUninitialize};
 
 pub mod audio;
 mod com_util;
+mod error;
 mod event;
+#[cfg(feature = "futures")]
+pub mod futures;
+pub mod lexicon;
 pub mod stt;
+#[cfg(feature = "test-support")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-support")))]
+pub mod testing;
 mod token;
 pub mod tts;
 
 #[cfg(feature = "tokio")]
 pub mod tokio;
 
-/// The error type returned by SAPI functions and methods.
-pub type Error = windows::core::Error;
+pub use error::Error;
 
 /// The type returned by SAPI functions and methods.
-pub type Result<T> = windows::core::Result<T>;
+pub type Result<T> = std::result::Result<T, Error>;
 
 /// Initializes SAPI on the current thread. This function must be called for every thread that
 /// intends to use SAPI.
 pub fn initialize() -> Result<()> {
-    unsafe { CoInitialize(null()) }
+    unsafe { CoInitialize(null()) }?;
+    Ok(())
 }
 
 /// Deinitializes SAPI for the current thread. This function must be called for every thread that
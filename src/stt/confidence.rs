@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+
+use windows as Windows;
+use Windows::Win32::Media::Speech::{SP_LOW_CONFIDENCE, SP_NORMAL_CONFIDENCE};
+
+use super::{Phrase, Recognition};
+
+/// The engine's coarse assessment of how likely it is that a recognition is correct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[allow(missing_docs)]
+pub enum ConfidenceLevel {
+    Low,
+    Normal,
+    High,
+}
+
+impl ConfidenceLevel {
+    fn from_sapi(level: i8) -> Self {
+        match level as i32 {
+            SP_LOW_CONFIDENCE => Self::Low,
+            level if level == SP_NORMAL_CONFIDENCE as i32 => Self::Normal,
+            _ => Self::High,
+        }
+    }
+}
+
+/// The engine's confidence in a recognized phrase, word, or semantic value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Confidence {
+    /// The coarse confidence level.
+    pub level: ConfidenceLevel,
+    /// The numeric confidence score. The range of the score depends on the engine.
+    pub score: f32,
+}
+
+impl Confidence {
+    pub(crate) fn from_sapi(level: i8, score: f32) -> Self {
+        Self {
+            level: ConfidenceLevel::from_sapi(level),
+            score,
+        }
+    }
+}
+
+/// The minimum confidence a phrase must have to be accepted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfidenceThreshold {
+    /// The phrase must have at least the given confidence level.
+    Level(ConfidenceLevel),
+    /// The phrase must have at least the given confidence score.
+    Score(f32),
+}
+
+impl ConfidenceThreshold {
+    fn is_met_by(&self, confidence: &Confidence) -> bool {
+        match self {
+            Self::Level(level) => confidence.level >= *level,
+            Self::Score(score) => confidence.score >= *score,
+        }
+    }
+}
+
+pub(crate) struct ConfidenceFilter {
+    min: Option<ConfidenceThreshold>,
+    rule_min: HashMap<OsString, ConfidenceThreshold>,
+}
+
+impl ConfidenceFilter {
+    pub fn new() -> Self {
+        Self {
+            min: None,
+            rule_min: HashMap::new(),
+        }
+    }
+
+    pub fn set_min(&mut self, threshold: Option<ConfidenceThreshold>) {
+        self.min = threshold;
+    }
+
+    pub fn set_rule_min(&mut self, rule: OsString, threshold: Option<ConfidenceThreshold>) {
+        match threshold {
+            Some(threshold) => self.rule_min.insert(rule, threshold),
+            None => self.rule_min.remove(&rule),
+        };
+    }
+
+    pub fn classify(&self, phrase: Phrase) -> Recognition {
+        let threshold = phrase
+            .rule
+            .name
+            .as_ref()
+            .and_then(|name| self.rule_min.get(name))
+            .or(self.min.as_ref());
+        match threshold {
+            Some(threshold) if !threshold.is_met_by(&phrase.confidence) => {
+                Recognition::Rejected(phrase)
+            }
+            _ => Recognition::Accepted(phrase),
+        }
+    }
+}
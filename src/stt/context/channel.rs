@@ -0,0 +1,152 @@
+use std::ops::Deref;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+
+use windows as Windows;
+use Windows::Win32::Foundation::E_INVALIDARG;
+
+use crate::stt::{Interference, Phrase, Recognition, RecognizerBase};
+use crate::Result;
+
+use super::{AudioLevel, Context, ContextEvent, EventHandler, EventfulContext};
+
+/// Specifies what a [`ChannelContext`] does with a new item when its channel is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Silently drop the new item.
+    DropNewest,
+    /// Wait until the receiver makes room for the new item. The events are delivered on a thread
+    /// owned by SAPI, which is blocked in the meantime, so the context will not deliver any other
+    /// events until then.
+    Block,
+}
+
+/// A recognition context that sends the recognized phrases, or the events raised in it, to a
+/// bounded [`std::sync::mpsc`] channel.
+///
+/// The capacity of the channel must be at least 1. If the receiver has been dropped, the items are
+/// discarded.
+pub struct ChannelContext {
+    base: EventfulContext,
+}
+
+impl ChannelContext {
+    /// Creates a new recognition context for the given recognizer, paired with a receiver for the
+    /// phrases it recognizes. Phrases rejected for having less than the minimum confidence are
+    /// not sent. Up to the given number of phrases can wait in the channel; after that, the new
+    /// phrases are handled according to the given policy.
+    pub fn new(
+        recognizer: &RecognizerBase,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Result<(Self, Receiver<Phrase>)> {
+        Self::with_handler(recognizer, capacity, policy)
+    }
+
+    /// Creates a new recognition context for the given recognizer, paired with a receiver for all
+    /// the events raised in it. Apart from the recognition outcomes, only the events enabled with
+    /// [`Context::set_interest`] are sent. Up to the given number of events can wait in the
+    /// channel; after that, the new events are handled according to the given policy.
+    pub fn with_events(
+        recognizer: &RecognizerBase,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Result<(Self, Receiver<ContextEvent>)> {
+        Self::with_handler(recognizer, capacity, policy)
+    }
+
+    fn with_handler<T: Send + 'static>(
+        recognizer: &RecognizerBase,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Result<(Self, Receiver<T>)>
+    where
+        ChannelHandler<T>: EventHandler,
+    {
+        // A channel without capacity would drop or block on every item the receiver isn't
+        // already waiting for
+        if capacity == 0 {
+            return Err(E_INVALIDARG.into());
+        }
+        let (tx, rx) = sync_channel(capacity);
+        let handler = ChannelHandler { tx, policy };
+        Ok((
+            Self {
+                base: EventfulContext::new(recognizer, handler)?,
+            },
+            rx,
+        ))
+    }
+}
+
+impl Deref for ChannelContext {
+    type Target = Context;
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+struct ChannelHandler<T> {
+    tx: SyncSender<T>,
+    policy: OverflowPolicy,
+}
+
+impl<T> ChannelHandler<T> {
+    fn send(&self, item: T) {
+        match self.policy {
+            OverflowPolicy::DropNewest => {
+                let _ = self.tx.try_send(item);
+            }
+            OverflowPolicy::Block => {
+                let _ = self.tx.send(item);
+            }
+        }
+    }
+}
+
+impl EventHandler for ChannelHandler<Phrase> {
+    fn on_recognition(&self, phrase: Phrase) {
+        self.send(phrase)
+    }
+}
+
+impl EventHandler for ChannelHandler<ContextEvent> {
+    fn on_recognition(&self, phrase: Phrase) {
+        self.send(ContextEvent::Recognition(Recognition::Accepted(phrase)))
+    }
+
+    fn on_rejection(&self, phrase: Phrase) {
+        self.send(ContextEvent::Recognition(Recognition::Rejected(phrase)))
+    }
+
+    fn on_hypothesis(&self, phrase: Phrase) {
+        self.send(ContextEvent::Hypothesis(phrase))
+    }
+
+    fn on_false_recognition(&self, phrase: Option<Phrase>) {
+        self.send(ContextEvent::Recognition(Recognition::FalseRecognition(
+            phrase,
+        )))
+    }
+
+    fn on_interference(&self, interference: Interference) {
+        self.send(ContextEvent::Recognition(Recognition::Interference(
+            interference,
+        )))
+    }
+
+    fn on_sound_start(&self, byte_offset: u64) {
+        self.send(ContextEvent::SoundStart { byte_offset })
+    }
+
+    fn on_sound_end(&self, byte_offset: u64) {
+        self.send(ContextEvent::SoundEnd { byte_offset })
+    }
+
+    fn on_phrase_start(&self, byte_offset: u64) {
+        self.send(ContextEvent::PhraseStart { byte_offset })
+    }
+
+    fn on_audio_level(&self, level: AudioLevel) {
+        self.send(ContextEvent::AudioLevel(level))
+    }
+}
@@ -1,18 +1,54 @@
 use std::ops::Deref;
+use std::sync::{Arc, Mutex};
 
 use windows as Windows;
 use Windows::core::Interface;
 
 use crate::event::{Event, EventSink, EventSource};
-use crate::stt::{Phrase, Recognizer};
+use crate::stt::{ConfidenceFilter, Interference, Phrase, Recognition, RecognizerBase};
 use crate::Result;
 
-use super::Context;
+use super::{decode_event, AudioLevel, Context, ContextEvent, EventInterest};
 
 /// The handler [`EventfulContext`] will call.
 pub trait EventHandler: Sync {
     /// Called when the engine has successfully recognized a phrase.
     fn on_recognition(&self, phrase: Phrase);
+
+    /// Called when the engine has recognized a phrase with less than the minimum confidence
+    /// configured in the context.
+    fn on_rejection(&self, _phrase: Phrase) {}
+
+    /// Called when the engine has formed a hypothesis about the phrase the user is still speaking.
+    /// Only called if hypotheses are enabled with [`Context::set_interest`].
+    fn on_hypothesis(&self, _phrase: Phrase) {}
+
+    /// Called when the engine has heard speech that didn't match any of the enabled rules, with
+    /// the partial phrase the engine was able to recognize, if any.
+    fn on_false_recognition(&self, _phrase: Option<Phrase>) {}
+
+    /// Called when the engine has detected a problem with the audio that interferes with the
+    /// recognition.
+    fn on_interference(&self, _interference: Interference) {}
+
+    /// Called when the engine starts hearing sound, with the offset in bytes of the audio stream
+    /// where the sound starts. Only called if voice activity is enabled with
+    /// [`Context::set_interest`].
+    fn on_sound_start(&self, _byte_offset: u64) {}
+
+    /// Called when the engine stops hearing sound, with the offset in bytes of the audio stream
+    /// where the sound ends. Only called if voice activity is enabled with
+    /// [`Context::set_interest`].
+    fn on_sound_end(&self, _byte_offset: u64) {}
+
+    /// Called when the engine detects the start of a phrase, with the offset in bytes of the audio
+    /// stream where the phrase starts. Only called if voice activity is enabled with
+    /// [`Context::set_interest`].
+    fn on_phrase_start(&self, _byte_offset: u64) {}
+
+    /// Called periodically with the level of the audio the engine is hearing. Only called if audio
+    /// levels are enabled with [`Context::set_interest`].
+    fn on_audio_level(&self, _level: AudioLevel) {}
 }
 
 impl<F: Fn(Phrase) + Sync> EventHandler for F {
@@ -30,25 +66,98 @@ pub struct EventfulContext {
 impl EventfulContext {
     /// Creates a new recognition context for the given recognizer, configured to call the given
     /// handler whenever a phrase from this context is recognized.
-    pub fn new<E: EventHandler + 'static>(recognizer: &Recognizer, handler: E) -> Result<Self> {
+    pub fn new<E: EventHandler + 'static>(recognizer: &RecognizerBase, handler: E) -> Result<Self> {
         let intf = unsafe { recognizer.intf.CreateRecoContext() }?;
+        let filter = Arc::new(Mutex::new(ConfidenceFilter::new()));
+        let sink_filter = filter.clone();
         EventSink::new(EventSource::from_sapi(intf.cast()?), move |event| {
-            if let Event::Recognition(result) = event {
-                let phrase = Phrase::from_sapi(result)?;
-                handler.on_recognition(phrase);
-            }
-            Ok(())
+            dispatch(&handler, &sink_filter, event)
         })
-        .install(None)?;
+        .install(Some(&EventInterest::default().to_sapi()))?;
         Ok(Self {
-            base: Context::new(intf, recognizer.pauser.clone()),
+            base: Context::new(intf, recognizer.pauser.clone(), filter),
         })
     }
 }
 
+fn dispatch<E: EventHandler>(
+    handler: &E,
+    filter: &Mutex<ConfidenceFilter>,
+    event: Event,
+) -> Result<()> {
+    match decode_event(filter, event)? {
+        Some(ContextEvent::Recognition(recognition)) => match recognition {
+            Recognition::Accepted(phrase) => handler.on_recognition(phrase),
+            Recognition::Rejected(phrase) => handler.on_rejection(phrase),
+            Recognition::FalseRecognition(phrase) => handler.on_false_recognition(phrase),
+            Recognition::Interference(interference) => handler.on_interference(interference),
+        },
+        Some(ContextEvent::Hypothesis(phrase)) => handler.on_hypothesis(phrase),
+        Some(ContextEvent::SoundStart { byte_offset }) => handler.on_sound_start(byte_offset),
+        Some(ContextEvent::SoundEnd { byte_offset }) => handler.on_sound_end(byte_offset),
+        Some(ContextEvent::PhraseStart { byte_offset }) => handler.on_phrase_start(byte_offset),
+        Some(ContextEvent::AudioLevel(level)) => handler.on_audio_level(level),
+        None => (),
+    }
+    Ok(())
+}
+
 impl Deref for EventfulContext {
     type Target = Context;
     fn deref(&self) -> &Self::Target {
         &self.base
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<ContextEvent>>);
+
+    impl EventHandler for Recorder {
+        fn on_recognition(&self, phrase: Phrase) {
+            self.record(ContextEvent::Recognition(Recognition::Accepted(phrase)))
+        }
+
+        fn on_sound_start(&self, byte_offset: u64) {
+            self.record(ContextEvent::SoundStart { byte_offset })
+        }
+
+        fn on_sound_end(&self, byte_offset: u64) {
+            self.record(ContextEvent::SoundEnd { byte_offset })
+        }
+
+        fn on_phrase_start(&self, byte_offset: u64) {
+            self.record(ContextEvent::PhraseStart { byte_offset })
+        }
+    }
+
+    impl Recorder {
+        fn record(&self, event: ContextEvent) {
+            self.0.lock().unwrap().push(event)
+        }
+    }
+
+    #[test]
+    fn test_dispatch_voice_activity() {
+        let handler = Recorder::default();
+        let filter = Mutex::new(ConfidenceFilter::new());
+        for event in [
+            Event::SoundStart(16),
+            Event::PhraseStart(32),
+            Event::SoundEnd(64),
+        ] {
+            dispatch(&handler, &filter, event).unwrap();
+        }
+        assert_eq!(
+            vec![
+                ContextEvent::SoundStart { byte_offset: 16 },
+                ContextEvent::PhraseStart { byte_offset: 32 },
+                ContextEvent::SoundEnd { byte_offset: 64 },
+            ],
+            *handler.0.lock().unwrap()
+        );
+    }
+}
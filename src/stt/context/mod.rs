@@ -1,35 +1,177 @@
+use std::ffi::OsString;
+use std::ptr::null;
+use std::sync::{Arc, Mutex};
+
 use windows as Windows;
-use Windows::Win32::Media::Speech::{ISpRecoContext, SPCS_DISABLED, SPCS_ENABLED};
+use Windows::core::Interface;
+use Windows::Win32::Media::Speech::{
+    ISpRecoContext, SPAO_NONE, SPAO_RETAIN_AUDIO, SPCS_DISABLED, SPCS_ENABLED,
+    SPEI_FALSE_RECOGNITION, SPEI_HYPOTHESIS, SPEI_INTERFERENCE, SPEI_PHRASE_START,
+    SPEI_RECOGNITION, SPEI_SOUND_END, SPEI_SOUND_START, SPEI_SR_AUDIO_LEVEL, SPEVENTENUM,
+};
 
 use crate::com_util::Intf;
+use crate::event::{set_interest, Event};
 use crate::Result;
 
-use super::{GrammarBuilder, RecognitionPauser};
+use super::{
+    ConfidenceFilter, ConfidenceThreshold, GrammarBuilder, Interference, Phrase, Recognition,
+    RecognitionPauser,
+};
 
+mod channel;
 mod event;
 mod sync;
 
+pub use channel::{ChannelContext, OverflowPolicy};
 pub use event::{EventHandler, EventfulContext};
-pub use sync::SyncContext;
+pub use sync::{ContextEvents, Interrupter, Recognitions, SyncContext};
+
+/// Specifies which optional events a context should receive from the engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EventInterest {
+    /// Receive hypotheses, i.e. partial phrases the engine reports while the user is speaking.
+    pub hypotheses: bool,
+    /// Receive false recognitions, i.e. speech that didn't match any of the enabled rules.
+    pub false_recognitions: bool,
+    /// Receive notifications about problems with the audio that interfere with the recognition.
+    pub interference: bool,
+    /// Receive notifications when the engine starts or stops hearing sound, and when it detects
+    /// the start of a phrase.
+    pub voice_activity: bool,
+    /// Receive periodic notifications about the level of the audio the engine is hearing.
+    pub audio_level: bool,
+}
+
+impl EventInterest {
+    fn to_sapi(self) -> Vec<SPEVENTENUM> {
+        let mut events = vec![SPEI_RECOGNITION];
+        if self.hypotheses {
+            events.push(SPEI_HYPOTHESIS);
+        }
+        if self.false_recognitions {
+            events.push(SPEI_FALSE_RECOGNITION);
+        }
+        if self.interference {
+            events.push(SPEI_INTERFERENCE);
+        }
+        if self.voice_activity {
+            events.extend([SPEI_SOUND_START, SPEI_SOUND_END, SPEI_PHRASE_START]);
+        }
+        if self.audio_level {
+            events.push(SPEI_SR_AUDIO_LEVEL);
+        }
+        events
+    }
+}
+
+/// The level of the audio the engine is hearing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct AudioLevel {
+    /// The audio level, in the range from 0 to 100.
+    pub level: u32,
+    /// The offset in bytes of the audio stream where the level was measured.
+    pub byte_offset: u64,
+}
+
+/// An event the engine has raised in a recognition context.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContextEvent {
+    /// The engine has concluded an attempt to recognize a phrase.
+    Recognition(Recognition),
+    /// The engine has formed a hypothesis about the phrase the user is still speaking. Only
+    /// raised if hypotheses are enabled with [`Context::set_interest`].
+    Hypothesis(Phrase),
+    /// The engine has started hearing sound. Only raised if voice activity is enabled with
+    /// [`Context::set_interest`].
+    SoundStart {
+        /// The offset in bytes of the audio stream where the sound starts.
+        byte_offset: u64,
+    },
+    /// The engine has stopped hearing sound. Only raised if voice activity is enabled with
+    /// [`Context::set_interest`].
+    SoundEnd {
+        /// The offset in bytes of the audio stream where the sound ends.
+        byte_offset: u64,
+    },
+    /// The engine has detected the start of a phrase. Only raised if voice activity is enabled
+    /// with [`Context::set_interest`].
+    PhraseStart {
+        /// The offset in bytes of the audio stream where the phrase starts.
+        byte_offset: u64,
+    },
+    /// The engine has measured the level of the audio it is hearing. Only raised if audio levels
+    /// are enabled with [`Context::set_interest`].
+    AudioLevel(AudioLevel),
+}
 
 /// Provides the common API shared across different kinds of contexts.
 pub struct Context {
     intf: Intf<ISpRecoContext>,
     pauser: RecognitionPauser,
+    filter: Arc<Mutex<ConfidenceFilter>>,
 }
 
 impl Context {
-    fn new(intf: ISpRecoContext, pauser: RecognitionPauser) -> Self {
+    fn new(
+        intf: ISpRecoContext,
+        pauser: RecognitionPauser,
+        filter: Arc<Mutex<ConfidenceFilter>>,
+    ) -> Self {
         Self {
             intf: Intf(intf),
             pauser,
+            filter,
         }
     }
 
     /// Enables or disables the recognition of rules from all grammars loaded into this context.
     pub fn set_enabled(&self, enabled: bool) -> Result<()> {
         let state = if enabled { SPCS_ENABLED } else { SPCS_DISABLED };
-        unsafe { self.intf.SetContextState(state) }
+        unsafe { self.intf.SetContextState(state) }?;
+        Ok(())
+    }
+
+    /// Configures which optional events this context should receive. By default, the context
+    /// doesn't receive any of them.
+    pub fn set_interest(&self, interest: EventInterest) -> Result<()> {
+        set_interest(&self.intf.cast()?, &interest.to_sapi())
+    }
+
+    /// Sets the minimum confidence a phrase recognized in this context must have to be accepted,
+    /// or clears it if `None`. Phrases with less confidence will be reported as rejected.
+    pub fn set_min_confidence(&self, threshold: Option<ConfidenceThreshold>) {
+        self.filter.lock().unwrap().set_min(threshold);
+    }
+
+    /// Sets the minimum confidence a phrase matching the top-level rule with the given name must
+    /// have to be accepted, or clears it if `None`. This overrides the minimum confidence set for
+    /// the whole context.
+    pub fn set_rule_min_confidence<S: Into<OsString>>(
+        &self,
+        rule: S,
+        threshold: Option<ConfidenceThreshold>,
+    ) {
+        self.filter
+            .lock()
+            .unwrap()
+            .set_rule_min(rule.into(), threshold);
+    }
+
+    /// Sets the maximum number of alternates the engine will provide for each phrase recognized in
+    /// this context. The alternates are available in
+    /// [`Phrase::alternates`](crate::stt::Phrase::alternates).
+    pub fn set_max_alternates(&self, count: u32) -> Result<()> {
+        unsafe { self.intf.SetMaxAlternates(count) }?;
+        Ok(())
+    }
+
+    /// Enables or disables retaining the audio of the phrases recognized in this context. The
+    /// retained audio is available in [`Phrase::audio`](crate::stt::Phrase::audio).
+    pub fn set_retain_audio(&self, retain: bool) -> Result<()> {
+        let options = if retain { SPAO_RETAIN_AUDIO } else { SPAO_NONE };
+        unsafe { self.intf.SetAudioOptions(options, null(), null()) }?;
+        Ok(())
     }
 
     /// Creates a [`GrammarBuilder`] that will construct and load a grammar into this context.
@@ -37,3 +179,35 @@ impl Context {
         GrammarBuilder::new(self.intf.clone(), self.pauser.clone())
     }
 }
+
+fn decode_event(filter: &Mutex<ConfidenceFilter>, event: Event) -> Result<Option<ContextEvent>> {
+    Ok(match event {
+        Event::Hypothesis(result) => {
+            Some(ContextEvent::Hypothesis(Phrase::from_sapi_partial(result)?))
+        }
+        Event::SoundStart(byte_offset) => Some(ContextEvent::SoundStart { byte_offset }),
+        Event::SoundEnd(byte_offset) => Some(ContextEvent::SoundEnd { byte_offset }),
+        Event::PhraseStart(byte_offset) => Some(ContextEvent::PhraseStart { byte_offset }),
+        Event::AudioLevel(level, byte_offset) => {
+            Some(ContextEvent::AudioLevel(AudioLevel { level, byte_offset }))
+        }
+        event => decode_outcome(filter, event)?.map(ContextEvent::Recognition),
+    })
+}
+
+// Decodes the events that conclude an attempt to recognize a phrase
+fn decode_outcome(filter: &Mutex<ConfidenceFilter>, event: Event) -> Result<Option<Recognition>> {
+    Ok(match event {
+        Event::Recognition(result) => {
+            let phrase = Phrase::from_sapi(result)?;
+            Some(filter.lock().unwrap().classify(phrase))
+        }
+        Event::FalseRecognition(result) => Some(Recognition::FalseRecognition(
+            Phrase::from_sapi_false(result)?,
+        )),
+        Event::Interference(interference) => {
+            Interference::from_sapi(interference).map(Recognition::Interference)
+        }
+        _ => None,
+    })
+}
@@ -1,57 +1,298 @@
 use std::ops::Deref;
-use std::time::Duration;
+use std::ptr::null;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use windows as Windows;
-use Windows::core::Interface;
+use Windows::core::{Handle, Interface};
+use Windows::Win32::Foundation::{CloseHandle, HANDLE, PWSTR, WAIT_FAILED};
+use Windows::Win32::System::Threading::{
+    CreateEventW, ResetEvent, SetEvent, WaitForMultipleObjects, WAIT_OBJECT_0,
+};
 
-use crate::event::{Event, EventSource};
-use crate::stt::{Phrase, Recognizer};
+use crate::event::{set_interest, EventSource};
+use crate::stt::{
+    ConfidenceFilter, Intents, Phrase, Recognition, RecognizedIntent, RecognizerBase,
+};
 use crate::Result;
 
-use super::Context;
+use super::{decode_event, Context, ContextEvent, EventInterest};
 
 /// A recognition context that blocks the current thread until the engine recognizes a phrase.
 pub struct SyncContext {
     base: Context,
     event_src: EventSource,
+    interrupt: Arc<InterruptEvent>,
 }
 
 impl SyncContext {
     /// Creates a new recognition context for the given recognizer.
-    pub fn new(recognizer: &Recognizer) -> Result<Self> {
+    pub fn new(recognizer: &RecognizerBase) -> Result<Self> {
         let intf = unsafe { recognizer.intf.CreateRecoContext() }?;
         unsafe { intf.SetNotifyWin32Event() }?;
+        set_interest(&intf.cast()?, &EventInterest::default().to_sapi())?;
         Ok(SyncContext {
             event_src: EventSource::from_sapi(intf.cast()?),
-            base: Context::new(intf, recognizer.pauser.clone()),
+            interrupt: Arc::new(InterruptEvent::new()?),
+            base: Context::new(
+                intf,
+                recognizer.pauser.clone(),
+                Arc::new(Mutex::new(ConfidenceFilter::new())),
+            ),
         })
     }
 
     /// Blocks the current thread until the engine recognizes a phrase or until the given timeout
-    /// expires.
+    /// expires. Phrases rejected for having less than the minimum confidence are skipped.
     pub fn recognize(&self, timeout: Duration) -> Result<Option<Phrase>> {
-        let result = self.next_phrase()?;
-        if result.is_some() {
-            return Ok(result);
+        self.wait_for(timeout, |_| (), Recognition::accepted)
+    }
+
+    /// Blocks the current thread until the engine concludes an attempt to recognize a phrase, or
+    /// until the given timeout expires. Unlike [`recognize`](Self::recognize), this also returns
+    /// rejected phrases, false recognitions, and interference with the audio. False recognitions
+    /// and interference must be enabled with [`Context::set_interest`].
+    pub fn recognize_outcome(&self, timeout: Duration) -> Result<Option<Recognition>> {
+        self.wait_for(timeout, |_| (), Some)
+    }
+
+    /// Blocks the current thread until the engine recognizes a phrase or until the given timeout
+    /// expires, calling the given function with every hypothesis the engine forms in the meantime.
+    /// Hypotheses must be enabled with [`Context::set_interest`].
+    pub fn recognize_with_hypotheses<F: FnMut(Phrase)>(
+        &self,
+        timeout: Duration,
+        on_hypothesis: F,
+    ) -> Result<Option<Phrase>> {
+        self.wait_for(timeout, on_hypothesis, Recognition::accepted)
+    }
+
+    /// Blocks the current thread until the engine recognizes a phrase that can be decoded into one
+    /// of the given intents, or until the given timeout expires. Phrases that cannot be decoded
+    /// are skipped, as are phrases rejected for having less than the minimum confidence.
+    pub fn recognize_intent<T>(
+        &self,
+        intents: &Intents<T>,
+        timeout: Duration,
+    ) -> Result<Option<RecognizedIntent<T>>> {
+        self.wait_for(
+            timeout,
+            |_| (),
+            |recognition| intents.decode(recognition.accepted()?),
+        )
+    }
+
+    /// Returns an iterator over the events the engine raises in this context, which blocks the
+    /// current thread until the next event or until the given timeout expires. The timeout applies
+    /// to the whole iteration, not to each event. The iteration also ends when it's interrupted
+    /// with an [`Interrupter`] from another thread.
+    ///
+    /// Apart from the recognition outcomes, the iterator yields only the events enabled with
+    /// [`Context::set_interest`].
+    pub fn events(&self, timeout: Duration) -> ContextEvents {
+        self.iterate(timeout, Some(self.interrupt.generation()))
+    }
+
+    /// Returns an iterator over the phrases the engine recognizes in this context, which blocks the
+    /// current thread until the next phrase or until the given timeout expires. Phrases rejected
+    /// for having less than the minimum confidence are skipped. See [`events`](Self::events) for
+    /// how the iteration ends.
+    pub fn recognitions(&self, timeout: Duration) -> Recognitions {
+        Recognitions {
+            events: self.events(timeout),
         }
+    }
 
-        let timeout_ms: u32 = timeout.as_millis().try_into().unwrap_or(u32::MAX - 1);
-        unsafe { self.base.intf.WaitForNotifyEvent(timeout_ms) }?;
+    /// Returns a handle that can interrupt the iterations over the events of this context from
+    /// another thread.
+    pub fn interrupter(&self) -> Interrupter {
+        Interrupter {
+            event: self.interrupt.clone(),
+        }
+    }
 
-        return self.next_phrase();
+    fn iterate(&self, timeout: Duration, generation: Option<u64>) -> ContextEvents {
+        ContextEvents {
+            ctx: self,
+            deadline: Instant::now().checked_add(timeout),
+            timeout,
+            generation,
+            done: false,
+        }
     }
 
-    fn next_phrase(&self) -> Result<Option<Phrase>> {
-        while let Some(event) = self.event_src.next_event()? {
-            if let Event::Recognition(result) = event {
-                let phrase = Phrase::from_sapi(result)?;
-                return Ok(Some(phrase));
+    // Keeps waiting until the deadline for an outcome the selector accepts
+    fn wait_for<T, H: FnMut(Phrase), F: Fn(Recognition) -> Option<T>>(
+        &self,
+        timeout: Duration,
+        mut on_hypothesis: H,
+        select: F,
+    ) -> Result<Option<T>> {
+        for event in self.iterate(timeout, None) {
+            match event? {
+                ContextEvent::Hypothesis(phrase) => on_hypothesis(phrase),
+                ContextEvent::Recognition(recognition) => {
+                    if let Some(result) = select(recognition) {
+                        return Ok(Some(result));
+                    }
+                }
+                _ => (),
             }
         }
         Ok(None)
     }
 }
 
+/// An iterator over the events raised in a [`SyncContext`]. See [`SyncContext::events`].
+pub struct ContextEvents<'c> {
+    ctx: &'c SyncContext,
+    deadline: Option<Instant>,
+    timeout: Duration,
+    // The interruption count when the iteration started, or None if it can't be interrupted
+    generation: Option<u64>,
+    done: bool,
+}
+
+impl<'c> ContextEvents<'c> {
+    fn next_event(&mut self) -> Result<Option<ContextEvent>> {
+        loop {
+            if self.is_interrupted() {
+                return Ok(None);
+            }
+            while let Some(event) = self.ctx.event_src.next_event()? {
+                if let Some(event) = decode_event(&self.ctx.base.filter, event)? {
+                    return Ok(Some(event));
+                }
+            }
+
+            let remaining = match self.deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => self.timeout,
+            };
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+
+            let timeout_ms: u32 = remaining.as_millis().try_into().unwrap_or(u32::MAX - 1);
+            if self.generation.is_none() {
+                unsafe { self.ctx.base.intf.WaitForNotifyEvent(timeout_ms) }?;
+                continue;
+            }
+
+            // The interruption comes first, so that it wins if both events are signaled
+            let handles = [self.ctx.interrupt.handle, unsafe {
+                self.ctx.base.intf.GetNotifyEventHandle()
+            }];
+            let result = unsafe {
+                WaitForMultipleObjects(handles.len() as _, handles.as_ptr(), false, timeout_ms)
+            };
+            if result == WAIT_FAILED.0 {
+                return Err(windows::core::Error::from_win32().into());
+            }
+            if result == WAIT_OBJECT_0 && !self.is_interrupted() {
+                // The event is still signaled from an interruption of an earlier iteration
+                self.ctx.interrupt.reset();
+            }
+        }
+    }
+
+    fn is_interrupted(&self) -> bool {
+        self.generation.map_or(false, |generation| {
+            generation != self.ctx.interrupt.generation()
+        })
+    }
+}
+
+impl<'c> Iterator for ContextEvents<'c> {
+    type Item = Result<ContextEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_event().transpose();
+        // The iteration ends for good after an error, a timeout, or an interruption
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
+
+/// An iterator over the phrases recognized in a [`SyncContext`]. See
+/// [`SyncContext::recognitions`].
+pub struct Recognitions<'c> {
+    events: ContextEvents<'c>,
+}
+
+impl<'c> Iterator for Recognitions<'c> {
+    type Item = Result<Phrase>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next()? {
+                Ok(ContextEvent::Recognition(Recognition::Accepted(phrase))) => {
+                    return Some(Ok(phrase))
+                }
+                Ok(_) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// A handle that interrupts the iterations over the events of a [`SyncContext`], possibly from
+/// another thread. See [`SyncContext::interrupter`].
+#[derive(Clone)]
+pub struct Interrupter {
+    event: Arc<InterruptEvent>,
+}
+
+impl Interrupter {
+    /// Ends the iterations over the events of the context that are currently in progress. The
+    /// iterations started afterwards are not affected.
+    pub fn interrupt(&self) {
+        self.event.interrupt();
+    }
+}
+
+// A manual-reset Win32 event that wakes up the iterations, paired with a count of the
+// interruptions. An iteration only ends if the count has changed since it started, so an
+// interruption can't be lost to a reset or leak into a later iteration.
+struct InterruptEvent {
+    handle: HANDLE,
+    generation: AtomicU64,
+}
+
+impl InterruptEvent {
+    fn new() -> Result<Self> {
+        let handle = unsafe { CreateEventW(null(), true, false, PWSTR::default()) }.ok()?;
+        Ok(Self {
+            handle,
+            generation: AtomicU64::new(0),
+        })
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    fn interrupt(&self) {
+        // The count must change before the event is signaled, see ContextEvents::next_event
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        unsafe { SetEvent(self.handle) };
+    }
+
+    fn reset(&self) {
+        unsafe { ResetEvent(self.handle) };
+    }
+}
+
+impl Drop for InterruptEvent {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.handle) };
+    }
+}
+
 impl Deref for SyncContext {
     type Target = Context;
     fn deref(&self) -> &Self::Target {
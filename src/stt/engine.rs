@@ -0,0 +1,125 @@
+use std::ffi::OsString;
+
+use crate::token::{token_language, Category, Token, TokenSelector};
+use crate::Result;
+
+/// A speech recognition engine installed on the system.
+pub struct Engine {
+    pub(crate) token: Token,
+}
+
+impl Engine {
+    /// Returns the name of this engine.
+    pub fn name(&self) -> Option<OsString> {
+        self.token.attr("name").ok()
+    }
+
+    /// Returns the vendor of this engine.
+    pub fn vendor(&self) -> Option<OsString> {
+        self.token.attr("vendor").ok()
+    }
+
+    /// Returns the language of this engine. If the engine supports more than one language, the
+    /// primary one is returned.
+    pub fn language(&self) -> Option<OsString> {
+        token_language(&self.token)
+    }
+
+    /// Returns the value of the given attribute of this engine, e.g. `"Version"` or
+    /// `"SupportsDictation"`.
+    pub fn attribute<S: AsRef<str>>(&self, name: S) -> Option<OsString> {
+        self.token.attr(name.as_ref()).ok()
+    }
+}
+
+/// Encapsulates the criteria for selecting a speech recognition engine.
+pub struct EngineSelector {
+    selector: TokenSelector,
+}
+
+impl EngineSelector {
+    /// Creates a new, empty selector.
+    pub fn new() -> Self {
+        Self {
+            selector: TokenSelector::new(),
+        }
+    }
+
+    /// Returns a selector that requires the engine to have the given name, along with all the
+    /// previously specified conditions.
+    pub fn name_eq<S: AsRef<str>>(self, name: S) -> Self {
+        self.append_condition("name=", name.as_ref())
+    }
+
+    /// Returns a selector that requires the engine to have a name different from the one given
+    /// here, along with all the previously specified conditions.
+    pub fn name_ne<S: AsRef<str>>(self, name: S) -> Self {
+        self.append_condition("name!=", name.as_ref())
+    }
+
+    /// Returns a selector that requires the engine to have the given vendor, along with all the
+    /// previously specified conditions.
+    pub fn vendor_eq<S: AsRef<str>>(self, vendor: S) -> Self {
+        self.append_condition("vendor=", vendor.as_ref())
+    }
+
+    /// Returns a selector that requires the engine to have a vendor different from the one given
+    /// here, along with all the previously specified conditions.
+    pub fn vendor_ne<S: AsRef<str>>(self, vendor: S) -> Self {
+        self.append_condition("vendor!=", vendor.as_ref())
+    }
+
+    /// Returns a selector that requires the engine to support the given language, along with all
+    /// the previously specified conditions.
+    pub fn language_eq<S: AsRef<str>>(self, language: S) -> Self {
+        Self {
+            selector: self
+                .selector
+                .append_language_condition("language=", language.as_ref()),
+        }
+    }
+
+    /// Returns a selector that requires the engine not to support the given language, along with
+    /// all the previously specified conditions.
+    pub fn language_ne<S: AsRef<str>>(self, language: S) -> Self {
+        Self {
+            selector: self
+                .selector
+                .append_language_condition("language!=", language.as_ref()),
+        }
+    }
+
+    /// Returns a selector that requires the engine to have the given attribute, regardless of its
+    /// value, along with all the previously specified conditions.
+    pub fn has_attribute<S: AsRef<str>>(self, name: S) -> Self {
+        self.append_condition(name.as_ref(), "")
+    }
+
+    fn append_condition(self, prefix: &str, val: &str) -> Self {
+        Self {
+            selector: self.selector.append_condition(prefix, val),
+        }
+    }
+
+    pub(crate) fn into_sapi_expr(self) -> String {
+        self.selector.into_sapi_expr()
+    }
+}
+
+/// If successful, returns an iterator enumerating all the installed speech recognition engines
+/// that satisfy the given criteria.
+///
+/// All returned engines will satisfy the `required` criteria. The engines that satisfy the
+/// `optional` criteria will be returned before the rest.
+pub fn installed_engines(
+    required: Option<EngineSelector>,
+    optional: Option<EngineSelector>,
+) -> Result<impl Iterator<Item = Engine>> {
+    let category = Category::new(r"HKEY_LOCAL_MACHINE\SOFTWARE\Microsoft\Speech\Recognizers")?;
+    let tokens = category.enum_tokens(
+        required.map(EngineSelector::into_sapi_expr),
+        optional.map(EngineSelector::into_sapi_expr),
+    )?;
+
+    Ok(tokens.map(|token| Engine { token }))
+}
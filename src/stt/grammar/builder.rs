@@ -1,20 +1,20 @@
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
-use std::hash::Hash;
 use std::mem::ManuallyDrop;
 use std::ptr::{null, null_mut};
 
-use windows::core::HRESULT;
 use windows::Win32::Media::Speech::{
     ISpRecoContext, ISpRecoGrammar, SPRAF_Active, SPRAF_TopLevel, SPSTATEHANDLE__, SPWT_LEXICAL,
 };
 
 use crate::com_util::{opt_str_param, out_to_ret, Intf};
+use crate::error::SPERR_STATE_WITH_NO_ARCS;
 use crate::stt::semantics::SemanticProperty;
 use crate::stt::{RecognitionPauser, SemanticValue};
 use crate::Result;
 
-use super::{grammar_state, rule_state, Grammar, RepeatRange, Rule};
+use super::graph::{Node, NodeId, OptimizationReport, RuleGraph, RuleRef};
+use super::{grammar_state, next_grammar_id, rule_state, Grammar, RepeatRange, Rule};
 
 /// Helper type that constructs a grammar from a set of top-level rules.
 ///
@@ -34,11 +34,17 @@ use super::{grammar_state, rule_state, Grammar, RepeatRange, Rule};
 /// ```
 /// Given this rule, the engine will only recognize the phrases "good morning" and "good evening",
 /// but not "good", "morning", or "evening".
+///
+/// Large grammars, especially generated ones, often contain many identical rules or choices whose
+/// options start with the same words. The builder can run an optional optimization pass that
+/// shrinks such grammars before loading them. See [`optimize`](GrammarBuilder::optimize) for
+/// details.
 pub struct GrammarBuilder<'a> {
     intf: Intf<ISpRecoContext>,
     pauser: RecognitionPauser,
     top_rules: HashSet<RuleRef<'a>>,
     rule_names: HashMap<RuleRef<'a>, Cow<'a, str>>,
+    optimized: bool,
 }
 
 impl<'a> GrammarBuilder<'a> {
@@ -48,6 +54,7 @@ impl<'a> GrammarBuilder<'a> {
             pauser,
             top_rules: HashSet::new(),
             rule_names: HashMap::new(),
+            optimized: false,
         }
     }
 
@@ -76,18 +83,54 @@ impl<'a> GrammarBuilder<'a> {
         self
     }
 
+    /// Enables or disables the optimization pass that runs before the grammar is built.
+    ///
+    /// The optimization merges structurally equal rules, factors out the common prefixes of the
+    /// options in a choice, and collapses sequences and choices that contain only one rule. The
+    /// optimized grammar recognizes the same phrases and produces the same semantic information
+    /// as the original one. Top-level rules are always kept distinct, so their names can still be
+    /// used to enable or disable them.
+    pub fn optimize(&mut self, enabled: bool) -> &mut Self {
+        self.optimized = enabled;
+        self
+    }
+
+    /// Returns the size of the grammar defined by the added rules, both before and after the
+    /// optimization pass. The report is computed regardless of whether the optimization is
+    /// enabled.
+    pub fn optimization_report(&self) -> OptimizationReport {
+        let graph = self.rule_graph();
+        OptimizationReport {
+            before: graph.size(),
+            after: graph.optimize().size(),
+        }
+    }
+
     /// Builds the grammar from the given rules and loads it into the recognition context. The
     /// newly loaded grammar must be enabled before the engine will start recognizing phrases from
     /// it.
     pub fn build(&mut self) -> Result<Grammar> {
-        let grammar = unsafe { self.intf.CreateGrammar(0) }?;
+        let mut graph = self.rule_graph();
+        if self.optimized {
+            graph = graph.optimize();
+        }
+
+        let id = next_grammar_id();
+        let grammar = unsafe { self.intf.CreateGrammar(id) }?;
         let mut rule_builder = RecursiveRuleBuilder {
             intf: grammar.clone(),
-            owner: &self,
+            graph: &graph,
+            top_rules: graph
+                .top_rules()
+                .iter()
+                .map(|(id, name)| (*id, name.as_ref()))
+                .collect(),
             built_rules: HashMap::new(),
         };
-        for rule in self.top_rules.iter() {
-            rule_builder.build_rule(rule.0)?;
+        for (id, name) in graph.top_rules() {
+            rule_builder
+                .build_rule(*id)
+                .map_err(|err| err.in_rule(name.as_deref()))?;
         }
         unsafe { grammar.Commit(0) }?;
         unsafe { grammar.SetGrammarState(grammar_state(false)) }?;
@@ -95,56 +138,46 @@ impl<'a> GrammarBuilder<'a> {
         Ok(Grammar {
             intf: ManuallyDrop::new(Intf(grammar)),
             pauser: self.pauser.clone(),
+            id,
         })
     }
-}
 
-#[derive(Clone, Copy)]
-struct RuleRef<'a>(&'a Rule<'a>);
-
-impl<'a> PartialEq for RuleRef<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        std::ptr::eq(self.0, other.0)
-    }
-}
-
-impl<'a> Eq for RuleRef<'a> {}
-
-impl<'a> Hash for RuleRef<'a> {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        std::ptr::hash(self.0, state)
+    fn rule_graph(&self) -> RuleGraph<'a> {
+        RuleGraph::from_rules(
+            self.top_rules
+                .iter()
+                .map(|rule| (rule.0, self.rule_names.get(rule).cloned())),
+        )
     }
 }
 
 type State = *mut SPSTATEHANDLE__;
 
-// SPERR_ constants are absent from the windows crate
-const SPERR_STATE_WITH_NO_ARCS: HRESULT = HRESULT(0x80045062);
-
 struct RecursiveRuleBuilder<'a, 'b> {
     intf: ISpRecoGrammar,
-    owner: &'b GrammarBuilder<'a>,
-    built_rules: HashMap<RuleRef<'a>, State>,
+    graph: &'b RuleGraph<'a>,
+    top_rules: HashMap<NodeId, Option<&'b Cow<'a, str>>>,
+    built_rules: HashMap<NodeId, State>,
 }
 
 impl<'a, 'b> RecursiveRuleBuilder<'a, 'b> {
-    fn build_rule(&mut self, rule: &'a Rule<'a>) -> Result<State> {
-        let rule_ref = RuleRef(rule);
-        if let Some(state) = self.built_rules.get(&rule_ref) {
+    fn build_rule(&mut self, id: NodeId) -> Result<State> {
+        if let Some(state) = self.built_rules.get(&id) {
             return Ok(*state);
         }
 
-        let flags = if self.owner.top_rules.contains(&rule_ref) {
+        let top_rule = self.top_rules.get(&id);
+        let flags = if top_rule.is_some() {
             (SPRAF_TopLevel.0 | SPRAF_Active.0) as u32
         } else {
             0
         };
-        let id: u32 = (self.built_rules.len() + 1).try_into().unwrap();
+        let rule_id: u32 = (self.built_rules.len() + 1).try_into().unwrap();
         let init_state = unsafe {
             out_to_ret(|out| {
                 self.intf.GetRule(
-                    opt_str_param(self.owner.rule_names.get(&rule_ref)).abi(),
-                    id,
+                    opt_str_param(top_rule.copied().flatten()).abi(),
+                    rule_id,
                     flags,
                     true,
                     out,
@@ -152,14 +185,15 @@ impl<'a, 'b> RecursiveRuleBuilder<'a, 'b> {
             })
         }?;
 
-        self.built_rules.insert(rule_ref, init_state);
+        self.built_rules.insert(id, init_state);
 
-        match rule {
-            Rule::Text(text) => self.build_text(init_state, text)?,
-            Rule::Choice(options) => self.build_choice(init_state, options)?,
-            Rule::Sequence(parts) => self.build_sequence(init_state, parts)?,
-            Rule::Repeat(times, target) => self.build_repeat(init_state, times, target)?,
-            Rule::Semantic(sem_val, target) => self.build_semantic(init_state, sem_val, target)?,
+        let graph = self.graph;
+        match graph.node(id) {
+            Node::Text(text) => self.build_text(init_state, text)?,
+            Node::Choice(options) => self.build_choice(init_state, options)?,
+            Node::Sequence(parts) => self.build_sequence(init_state, parts)?,
+            Node::Repeat(times, target) => self.build_repeat(init_state, times, *target)?,
+            Node::Semantic(sem_val, target) => self.build_semantic(init_state, sem_val, *target)?,
         }
 
         Ok(init_state)
@@ -169,20 +203,20 @@ impl<'a, 'b> RecursiveRuleBuilder<'a, 'b> {
         self.text_arc(init_state, null_mut(), text.as_ref())
     }
 
-    fn build_choice(&mut self, init_state: State, options: &Cow<'a, [&Rule<'a>]>) -> Result<()> {
+    fn build_choice(&mut self, init_state: State, options: &[NodeId]) -> Result<()> {
         // This is what SAPI should do, but instead it causes an access violation on my machine
         if options.is_empty() {
             return Err(SPERR_STATE_WITH_NO_ARCS.into());
         }
 
         for option in options.iter() {
-            let child_state = self.build_rule(option)?;
+            let child_state = self.build_rule(*option)?;
             self.rule_arc(init_state, null_mut(), child_state, None)?;
         }
         Ok(())
     }
 
-    fn build_sequence(&mut self, init_state: State, parts: &Cow<'a, [&'a Rule<'a>]>) -> Result<()> {
+    fn build_sequence(&mut self, init_state: State, parts: &[NodeId]) -> Result<()> {
         // This is what SAPI should do, but instead it causes an access violation on my machine
         if parts.is_empty() {
             return Err(SPERR_STATE_WITH_NO_ARCS.into());
@@ -191,7 +225,7 @@ impl<'a, 'b> RecursiveRuleBuilder<'a, 'b> {
         let mut part_iter = parts.iter().peekable();
         let mut prev_state = init_state;
         while let Some(part) = part_iter.next() {
-            let child_state = self.build_rule(part)?;
+            let child_state = self.build_rule(*part)?;
             let next_state = if part_iter.peek().is_some() {
                 self.create_state(prev_state)?
             } else {
@@ -207,7 +241,7 @@ impl<'a, 'b> RecursiveRuleBuilder<'a, 'b> {
         &mut self,
         init_state: State,
         times: &RepeatRange,
-        target: &'a Rule<'a>,
+        target: NodeId,
     ) -> Result<()> {
         let child_state = self.build_rule(target)?;
         let mut prev_state = init_state;
@@ -235,7 +269,7 @@ impl<'a, 'b> RecursiveRuleBuilder<'a, 'b> {
         &mut self,
         init_state: State,
         sem_val: &SemanticValue<Cow<'a, str>>,
-        target: &'a Rule<'a>,
+        target: NodeId,
     ) -> Result<()> {
         let child_state = self.build_rule(target)?;
         let property = SemanticProperty::new(sem_val);
@@ -250,7 +284,8 @@ impl<'a, 'b> RecursiveRuleBuilder<'a, 'b> {
         unsafe {
             self.intf
                 .AddWordTransition(from_state, to_state, text, " ", SPWT_LEXICAL, 1.0, null())
-        }
+        }?;
+        Ok(())
     }
 
     fn rule_arc(
@@ -267,13 +302,15 @@ impl<'a, 'b> RecursiveRuleBuilder<'a, 'b> {
         unsafe {
             self.intf
                 .AddRuleTransition(from_state, to_state, child_state, 1.0, prop_ptr)
-        }
+        }?;
+        Ok(())
     }
 
     fn epsilon_arc(&mut self, from_state: State, to_state: State) -> Result<()> {
         unsafe {
             self.intf
                 .AddWordTransition(from_state, to_state, None, None, SPWT_LEXICAL, 1.0, null())
-        }
+        }?;
+        Ok(())
     }
 }
@@ -0,0 +1,640 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use crate::stt::SemanticValue;
+
+use super::{RepeatRange, Rule};
+
+pub(super) type NodeId = usize;
+
+/// A rule from the grammar, with its sub-rules replaced by indices into the owning graph.
+#[derive(Debug, Clone)]
+pub(super) enum Node<'a> {
+    Text(Cow<'a, str>),
+    Choice(Vec<NodeId>),
+    Sequence(Vec<NodeId>),
+    Repeat(RepeatRange, NodeId),
+    Semantic(SemanticValue<Cow<'a, str>>, NodeId),
+}
+
+impl<'a> PartialEq for Node<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Node::Text(lhs), Node::Text(rhs)) => lhs == rhs,
+            (Node::Choice(lhs), Node::Choice(rhs)) => lhs == rhs,
+            (Node::Sequence(lhs), Node::Sequence(rhs)) => lhs == rhs,
+            (Node::Repeat(lhs_times, lhs), Node::Repeat(rhs_times, rhs)) => {
+                lhs_times == rhs_times && lhs == rhs
+            }
+            (Node::Semantic(lhs_val, lhs), Node::Semantic(rhs_val, rhs)) => {
+                ValueKey::new(lhs_val) == ValueKey::new(rhs_val) && lhs == rhs
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Eq for Node<'a> {}
+
+impl<'a> Hash for Node<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Node::Text(text) => text.hash(state),
+            Node::Choice(options) => options.hash(state),
+            Node::Sequence(parts) => parts.hash(state),
+            Node::Repeat(times, target) => {
+                times.hash(state);
+                target.hash(state);
+            }
+            Node::Semantic(value, target) => {
+                ValueKey::new(value).hash(state);
+                target.hash(state);
+            }
+        }
+    }
+}
+
+// Floating point values don't implement Eq and Hash, so compare them bitwise instead.
+#[derive(PartialEq, Eq, Hash)]
+enum ValueKey<'v> {
+    Bool(bool),
+    Int(i32),
+    Float(u32),
+    Double(u64),
+    String(&'v str),
+}
+
+impl<'v> ValueKey<'v> {
+    fn new(value: &'v SemanticValue<Cow<str>>) -> Self {
+        match value {
+            SemanticValue::Bool(b) => Self::Bool(*b),
+            SemanticValue::Int(i) => Self::Int(*i),
+            SemanticValue::Float(f) => Self::Float(f.to_bits()),
+            SemanticValue::Double(d) => Self::Double(d.to_bits()),
+            SemanticValue::String(s) => Self::String(s.as_ref()),
+        }
+    }
+}
+
+/// The size of a grammar, measured by the number of rules and transitions needed to build it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GrammarSize {
+    /// The number of rules in the grammar, including the top-level rules.
+    pub rules: usize,
+    /// The number of transitions between the states of all the rules in the grammar.
+    pub transitions: usize,
+}
+
+/// Describes the effect of the optimization pass on a grammar.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OptimizationReport {
+    /// The size of the grammar as defined by its rules.
+    pub before: GrammarSize,
+    /// The size of the grammar after the optimization pass.
+    pub after: GrammarSize,
+}
+
+/// The graph of rules reachable from the top-level rules of a grammar.
+#[derive(Debug, Default)]
+pub(crate) struct RuleGraph<'a> {
+    nodes: Vec<Node<'a>>,
+    top_rules: Vec<(NodeId, Option<Cow<'a, str>>)>,
+}
+
+impl<'a> RuleGraph<'a> {
+    /// Converts the given top-level rules and their names into a graph. Rules that are referenced
+    /// more than once will be represented by the same node.
+    pub(crate) fn from_rules<I>(top_rules: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a Rule<'a>, Option<Cow<'a, str>>)>,
+    {
+        let mut lowering = Lowering {
+            graph: RuleGraph::default(),
+            lowered: HashMap::new(),
+        };
+        for (rule, name) in top_rules {
+            let id = lowering.lower(rule);
+            lowering.graph.top_rules.push((id, name));
+        }
+        lowering.graph
+    }
+
+    pub(super) fn node(&self, id: NodeId) -> &Node<'a> {
+        &self.nodes[id]
+    }
+
+    pub(super) fn top_rules(&self) -> &[(NodeId, Option<Cow<'a, str>>)] {
+        &self.top_rules
+    }
+
+    /// Computes the size of the grammar this graph describes.
+    pub(super) fn size(&self) -> GrammarSize {
+        let mut size = GrammarSize::default();
+        let mut visited = HashSet::new();
+        let mut pending: Vec<NodeId> = self.top_rules.iter().map(|(id, _)| *id).collect();
+        while let Some(id) = pending.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            size.rules += 1;
+            match &self.nodes[id] {
+                // The engine needs a transition for every word of the text
+                Node::Text(text) => size.transitions += text.split_whitespace().count().max(1),
+                Node::Choice(children) | Node::Sequence(children) => {
+                    size.transitions += children.len();
+                    pending.extend(children.iter().copied());
+                }
+                Node::Repeat(times, target) => {
+                    size.transitions += times.max + times.max.saturating_sub(times.min);
+                    pending.push(*target);
+                }
+                Node::Semantic(_, target) => {
+                    size.transitions += 1;
+                    pending.push(*target);
+                }
+            }
+        }
+        size
+    }
+
+    /// Returns an equivalent graph where structurally equal rules are merged, the common prefixes
+    /// of choice options are factored out, and trivial sequences and choices are collapsed. The
+    /// optimized graph produces the same semantic information as this one.
+    pub(crate) fn optimize(&self) -> RuleGraph<'a> {
+        let mut optimizer = Optimizer {
+            source: self,
+            target: RuleGraph::default(),
+            interned: HashMap::new(),
+            mapped: HashMap::new(),
+        };
+        for (id, name) in self.top_rules.iter() {
+            let node = match optimizer.simplify(*id) {
+                Simplified::Ref(target_id) => optimizer.target.nodes[target_id].clone(),
+                Simplified::Node(node) => node,
+            };
+            let top_id = optimizer.intern_top(node);
+            optimizer.target.top_rules.push((top_id, name.clone()));
+        }
+        optimizer.target
+    }
+
+    fn push(&mut self, node: Node<'a>) -> NodeId {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+}
+
+struct Lowering<'a> {
+    graph: RuleGraph<'a>,
+    lowered: HashMap<RuleRef<'a>, NodeId>,
+}
+
+impl<'a> Lowering<'a> {
+    fn lower(&mut self, rule: &'a Rule<'a>) -> NodeId {
+        if let Some(id) = self.lowered.get(&RuleRef(rule)) {
+            return *id;
+        }
+
+        let node = match rule {
+            Rule::Text(text) => Node::Text(text.clone()),
+            Rule::Choice(options) => {
+                Node::Choice(options.iter().map(|option| self.lower(*option)).collect())
+            }
+            Rule::Sequence(parts) => {
+                Node::Sequence(parts.iter().map(|part| self.lower(*part)).collect())
+            }
+            Rule::Repeat(times, target) => Node::Repeat(times.clone(), self.lower(*target)),
+            Rule::Semantic(value, target) => Node::Semantic(value.clone(), self.lower(*target)),
+        };
+        let id = self.graph.push(node);
+        self.lowered.insert(RuleRef(rule), id);
+        id
+    }
+}
+
+/// Identifies a rule by its address, rather than its contents.
+#[derive(Clone, Copy)]
+pub(super) struct RuleRef<'a>(pub(super) &'a Rule<'a>);
+
+impl<'a> PartialEq for RuleRef<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl<'a> Eq for RuleRef<'a> {}
+
+impl<'a> Hash for RuleRef<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::ptr::hash(self.0, state)
+    }
+}
+
+enum Simplified<'a> {
+    /// The rule is equivalent to an existing node in the target graph
+    Ref(NodeId),
+    /// The rule needs a node with the given contents
+    Node(Node<'a>),
+}
+
+struct Optimizer<'a, 'g> {
+    source: &'g RuleGraph<'a>,
+    target: RuleGraph<'a>,
+    interned: HashMap<Node<'a>, NodeId>,
+    mapped: HashMap<NodeId, NodeId>,
+}
+
+impl<'a, 'g> Optimizer<'a, 'g> {
+    fn node_ref(&mut self, source_id: NodeId) -> NodeId {
+        if let Some(id) = self.mapped.get(&source_id) {
+            return *id;
+        }
+        let id = match self.simplify(source_id) {
+            Simplified::Ref(id) => id,
+            Simplified::Node(node) => self.intern(node),
+        };
+        self.mapped.insert(source_id, id);
+        id
+    }
+
+    fn intern(&mut self, node: Node<'a>) -> NodeId {
+        if let Some(id) = self.interned.get(&node) {
+            return *id;
+        }
+        let id = self.target.push(node.clone());
+        self.interned.insert(node, id);
+        id
+    }
+
+    // Every top-level rule needs its own node, because each of them can have a different name and
+    // the engine attributes a match to the innermost rule. The node is never interned, so the
+    // other rules can't end up referencing it.
+    fn intern_top(&mut self, node: Node<'a>) -> NodeId {
+        self.target.push(node)
+    }
+
+    fn resolve(&mut self, simplified: Simplified<'a>) -> NodeId {
+        match simplified {
+            Simplified::Ref(id) => id,
+            Simplified::Node(node) => self.intern(node),
+        }
+    }
+
+    fn simplify(&mut self, source_id: NodeId) -> Simplified<'a> {
+        let source = self.source;
+        match &source.nodes[source_id] {
+            Node::Text(text) => Simplified::Node(Node::Text(text.clone())),
+            Node::Choice(options) => {
+                let options = options.iter().map(|id| self.node_ref(*id)).collect();
+                self.simplify_choice(options)
+            }
+            Node::Sequence(parts) => {
+                let parts = parts.iter().map(|id| self.node_ref(*id)).collect();
+                self.simplify_sequence(parts)
+            }
+            Node::Repeat(times, target) => {
+                let target = self.node_ref(*target);
+                if times.min == 1 && times.max == 1 {
+                    Simplified::Ref(target)
+                } else {
+                    Simplified::Node(Node::Repeat(times.clone(), target))
+                }
+            }
+            Node::Semantic(value, target) => {
+                Simplified::Node(Node::Semantic(value.clone(), self.node_ref(*target)))
+            }
+        }
+    }
+
+    fn simplify_sequence(&mut self, parts: Vec<NodeId>) -> Simplified<'a> {
+        // Empty sequences are left alone, so that building them still fails
+        let mut flattened = Vec::with_capacity(parts.len());
+        for part in parts {
+            match &self.target.nodes[part] {
+                Node::Sequence(inner) if !inner.is_empty() => flattened.extend(inner.iter()),
+                _ => flattened.push(part),
+            }
+        }
+
+        let mut merged = Vec::with_capacity(flattened.len());
+        let mut pending_text: Option<String> = None;
+        for part in flattened {
+            if let Node::Text(text) = &self.target.nodes[part] {
+                match pending_text.as_mut() {
+                    Some(pending) => {
+                        pending.push(' ');
+                        pending.push_str(text);
+                    }
+                    None => pending_text = Some(text.to_string()),
+                }
+                continue;
+            }
+            if let Some(text) = pending_text.take() {
+                merged.push(self.intern(Node::Text(text.into())));
+            }
+            merged.push(part);
+        }
+        if let Some(text) = pending_text.take() {
+            merged.push(self.intern(Node::Text(text.into())));
+        }
+
+        if merged.len() == 1 {
+            Simplified::Ref(merged[0])
+        } else {
+            Simplified::Node(Node::Sequence(merged))
+        }
+    }
+
+    fn simplify_choice(&mut self, options: Vec<NodeId>) -> Simplified<'a> {
+        // Empty choices are left alone, so that building them still fails
+        let mut seen = HashSet::new();
+        let mut flattened = Vec::with_capacity(options.len());
+        for option in options {
+            match &self.target.nodes[option] {
+                Node::Choice(inner) if !inner.is_empty() => {
+                    for id in inner.iter() {
+                        if seen.insert(*id) {
+                            flattened.push(*id);
+                        }
+                    }
+                }
+                _ => {
+                    if seen.insert(option) {
+                        flattened.push(option);
+                    }
+                }
+            }
+        }
+
+        let factored = self.factor_prefixes(flattened);
+        if factored.len() == 1 {
+            Simplified::Ref(factored[0])
+        } else {
+            Simplified::Node(Node::Choice(factored))
+        }
+    }
+
+    fn factor_prefixes(&mut self, options: Vec<NodeId>) -> Vec<NodeId> {
+        // Group the options by their first part, keeping the groups in order of first appearance
+        let mut groups: Vec<PrefixGroup> = Vec::new();
+        let mut group_index: HashMap<NodeId, usize> = HashMap::new();
+        for option in options {
+            let (head, tail) = match &self.target.nodes[option] {
+                Node::Sequence(parts) if !parts.is_empty() => (parts[0], parts[1..].to_vec()),
+                _ => (option, Vec::new()),
+            };
+            match group_index.get(&head) {
+                Some(idx) => groups[*idx].tails.push(tail),
+                None => {
+                    group_index.insert(head, groups.len());
+                    groups.push(PrefixGroup {
+                        first_option: option,
+                        head,
+                        tails: vec![tail],
+                    });
+                }
+            }
+        }
+
+        let mut result = Vec::with_capacity(groups.len());
+        for group in groups {
+            if group.tails.len() == 1 {
+                result.push(group.first_option);
+                continue;
+            }
+
+            let is_optional = group.tails.iter().any(Vec::is_empty);
+            let mut rests = Vec::with_capacity(group.tails.len());
+            for tail in group.tails {
+                match tail.len() {
+                    0 => {}
+                    1 => rests.push(tail[0]),
+                    _ => rests.push(self.intern(Node::Sequence(tail))),
+                }
+            }
+            if rests.is_empty() {
+                result.push(group.head);
+                continue;
+            }
+
+            let simplified = self.simplify_choice(rests);
+            let mut rest = self.resolve(simplified);
+            if is_optional {
+                rest = self.intern(Node::Repeat(RepeatRange { min: 0, max: 1 }, rest));
+            }
+            let simplified = self.simplify_sequence(vec![group.head, rest]);
+            result.push(self.resolve(simplified));
+        }
+        result
+    }
+}
+
+struct PrefixGroup {
+    first_option: NodeId,
+    head: NodeId,
+    tails: Vec<Vec<NodeId>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stt::RuleArena;
+
+    use super::*;
+
+    fn graph<'a>(rules: Vec<&'a Rule<'a>>) -> RuleGraph<'a> {
+        RuleGraph::from_rules(rules.into_iter().map(|rule| (rule, None)))
+    }
+
+    fn top_node<'g, 'a>(graph: &'g RuleGraph<'a>) -> &'g Node<'a> {
+        graph.node(graph.top_rules()[0].0)
+    }
+
+    fn assert_same_matches(graph: &RuleGraph, optimized: &RuleGraph, utterances: &[&str]) {
+        for utterance in utterances {
+            assert_eq!(
+                graph.parse(utterance),
+                optimized.parse(utterance),
+                "{:?}",
+                utterance
+            );
+        }
+    }
+
+    #[test]
+    fn test_size_counts_words() {
+        let greeting = Rule::text("have a good day");
+        let size = graph(vec![&greeting]).size();
+        assert_eq!(
+            GrammarSize {
+                rules: 1,
+                transitions: 4
+            },
+            size
+        );
+    }
+
+    #[test]
+    fn test_sequence_text_merging() {
+        let have = Rule::text("have");
+        let good = Rule::text("a good");
+        let day = Rule::text("day");
+        let inner = Rule::sequence(vec![&good, &day]);
+        let greeting = Rule::sequence(vec![&have, &inner]);
+        let source = graph(vec![&greeting]);
+        let optimized = source.optimize();
+
+        assert_eq!(&Node::Text("have a good day".into()), top_node(&optimized));
+        assert_eq!(source.size().transitions, 8);
+        assert_eq!(optimized.size().transitions, 4);
+        assert_same_matches(
+            &source,
+            &optimized,
+            &["have a good day", "have a good", "have a day"],
+        );
+    }
+
+    #[test]
+    fn test_sequence_merges_text_around_semantics() {
+        let say = Rule::text("say");
+        let hello = Rule::text("hello");
+        let greeting = Rule::semantic("greeting", &hello);
+        let now = Rule::text("now");
+        let please = Rule::text("please");
+        let command = Rule::sequence(vec![&say, &greeting, &now, &please]);
+        let source = graph(vec![&command]);
+        let optimized = source.optimize();
+
+        match top_node(&optimized) {
+            Node::Sequence(parts) => {
+                assert_eq!(3, parts.len());
+                assert_eq!(&Node::Text("say".into()), optimized.node(parts[0]));
+                assert!(matches!(optimized.node(parts[1]), Node::Semantic(..)));
+                assert_eq!(&Node::Text("now please".into()), optimized.node(parts[2]));
+            }
+            node => panic!("unexpected node: {:?}", node),
+        }
+        assert_same_matches(
+            &source,
+            &optimized,
+            &["say hello now please", "say hello now", "say now please"],
+        );
+    }
+
+    #[test]
+    fn test_choice_dedup() {
+        let yes = Rule::text("yes");
+        let also_yes = Rule::text("yes");
+        let no = Rule::text("no");
+        let answer = Rule::choice(vec![&yes, &also_yes, &no]);
+        let source = graph(vec![&answer]);
+        let optimized = source.optimize();
+
+        match top_node(&optimized) {
+            Node::Choice(options) => {
+                let texts: Vec<&Node> = options.iter().map(|id| optimized.node(*id)).collect();
+                assert_eq!(
+                    vec![&Node::Text("yes".into()), &Node::Text("no".into())],
+                    texts
+                );
+            }
+            node => panic!("unexpected node: {:?}", node),
+        }
+        assert_same_matches(&source, &optimized, &["yes", "no", "maybe"]);
+    }
+
+    #[test]
+    fn test_choice_factoring_with_semantics() {
+        let arena = RuleArena::new();
+        let mut options = Vec::new();
+        for (idx, item) in ["salad", "soup", "salad"].iter().enumerate() {
+            options.push(arena.sequence(vec![
+                arena.text("serve"),
+                arena.semantic(idx as i32, arena.text(*item)),
+                arena.text("to"),
+                arena.text("Arthur"),
+            ]));
+        }
+        let top = arena.choice(options);
+        let source = RuleGraph::from_rules(vec![(top, Some("serve".into()))]);
+        let optimized = source.optimize();
+
+        assert_eq!(
+            GrammarSize {
+                rules: 19,
+                transitions: 30
+            },
+            source.size()
+        );
+        assert_eq!(
+            GrammarSize {
+                rules: 12,
+                transitions: 19
+            },
+            optimized.size()
+        );
+        assert_same_matches(
+            &source,
+            &optimized,
+            &[
+                "serve salad to Arthur",
+                "serve soup to Arthur",
+                "serve to Arthur",
+                "serve salad",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_top_rule_does_not_claim_shared_node() {
+        let please = Rule::text("please");
+        let polite = Rule::semantic("polite", &please);
+        let stop = Rule::text("stop");
+        let command = Rule::sequence(vec![&stop, &polite]);
+        let source = RuleGraph::from_rules(vec![
+            (&command, Some("command".into())),
+            (&polite, Some("polite".into())),
+        ]);
+        let optimized = source.optimize();
+
+        let (command_id, _) = optimized.top_rules()[0];
+        let (polite_id, _) = optimized.top_rules()[1];
+        match optimized.node(command_id) {
+            Node::Sequence(parts) => {
+                assert_eq!(2, parts.len());
+                assert_ne!(polite_id, parts[1]);
+                assert_eq!(optimized.node(polite_id), optimized.node(parts[1]));
+            }
+            node => panic!("unexpected node: {:?}", node),
+        }
+        assert_same_matches(&source, &optimized, &["stop please", "please", "stop"]);
+    }
+
+    #[test]
+    fn test_choice_prefix_factoring() {
+        let good = Rule::text("good");
+        let morning = Rule::text("morning");
+        let evening = Rule::text("evening");
+        let am = Rule::semantic("am", &morning);
+        let pm = Rule::semantic("pm", &evening);
+        let good_morning = Rule::sequence(vec![&good, &am]);
+        let good_evening = Rule::sequence(vec![&good, &pm]);
+        let greeting = Rule::choice(vec![&good_morning, &good_evening, &good]);
+        let source = graph(vec![&greeting]);
+        let optimized = source.optimize();
+
+        assert!(optimized.size().transitions < source.size().transitions);
+        assert_same_matches(
+            &source,
+            &optimized,
+            &[
+                "good morning",
+                "good evening",
+                "good",
+                "morning",
+                "good night",
+            ],
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use std::borrow::Cow;
+use std::ffi::OsString;
+
+use crate::stt::{SemanticTree, SemanticValue};
+
+use super::graph::{Node, NodeId, RuleGraph};
+
+/// The position in the utterance after a rule was matched, and the semantic information produced
+/// by the match.
+type Match = (usize, Vec<SemanticTree>);
+
+impl<'a> RuleGraph<'a> {
+    /// Matches the given utterance against the top-level rules, trying them in the order they were
+    /// added. On success, returns the name of the matching rule and the semantic information the
+    /// engine would produce for the utterance.
+    ///
+    /// Words are compared case-insensitively. If the utterance can be matched in more than one
+    /// way, the first match found is returned.
+    pub(crate) fn parse(&self, utterance: &str) -> Option<(Option<&str>, Vec<SemanticTree>)> {
+        let words: Vec<String> = utterance
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        for (id, name) in self.top_rules() {
+            let full_match = self
+                .match_node(*id, &words, 0)
+                .into_iter()
+                .find(|(end, _)| *end == words.len());
+            if let Some((_, semantics)) = full_match {
+                return Some((name.as_deref(), semantics));
+            }
+        }
+        None
+    }
+
+    fn match_node(&self, id: NodeId, words: &[String], start: usize) -> Vec<Match> {
+        match self.node(id) {
+            Node::Text(text) => match_text(text, words, start).into_iter().collect(),
+            Node::Choice(options) => options
+                .iter()
+                .flat_map(|option| self.match_node(*option, words, start))
+                .collect(),
+            Node::Sequence(parts) => {
+                let mut matches = vec![(start, Vec::new())];
+                for part in parts.iter() {
+                    matches = self.extend_matches(matches, *part, words);
+                }
+                matches
+            }
+            Node::Repeat(times, target) => {
+                let mut result = Vec::new();
+                let mut matches = vec![(start, Vec::new())];
+                for count in 0..=times.max {
+                    if count >= times.min {
+                        result.extend(matches.iter().cloned());
+                    }
+                    if count == times.max || matches.is_empty() {
+                        break;
+                    }
+                    matches = self.extend_matches(matches, *target, words);
+                }
+                result
+            }
+            Node::Semantic(value, target) => self
+                .match_node(*target, words, start)
+                .into_iter()
+                .map(|(end, children)| {
+                    let tree = SemanticTree {
+                        value: owned_value(value),
+                        children,
+                        confidence: None,
+                    };
+                    (end, vec![tree])
+                })
+                .collect(),
+        }
+    }
+
+    fn extend_matches(&self, matches: Vec<Match>, id: NodeId, words: &[String]) -> Vec<Match> {
+        let mut result = Vec::new();
+        for (end, semantics) in matches {
+            for (next_end, next_semantics) in self.match_node(id, words, end) {
+                let mut combined = semantics.clone();
+                combined.extend(next_semantics);
+                result.push((next_end, combined));
+            }
+        }
+        result
+    }
+}
+
+fn match_text(text: &str, words: &[String], start: usize) -> Option<Match> {
+    let mut pos = start;
+    for expected in text.split_whitespace() {
+        if words.get(pos)? != &expected.to_lowercase() {
+            return None;
+        }
+        pos += 1;
+    }
+    Some((pos, Vec::new()))
+}
+
+fn owned_value(value: &SemanticValue<Cow<str>>) -> SemanticValue<OsString> {
+    match value {
+        SemanticValue::Bool(b) => SemanticValue::Bool(*b),
+        SemanticValue::Int(i) => SemanticValue::Int(*i),
+        SemanticValue::Float(f) => SemanticValue::Float(*f),
+        SemanticValue::Double(d) => SemanticValue::Double(*d),
+        SemanticValue::String(s) => SemanticValue::String(OsString::from(s.as_ref())),
+    }
+}
@@ -1,5 +1,6 @@
 use std::mem::ManuallyDrop;
 use std::ptr::null_mut;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use windows as Windows;
 use Windows::Win32::Media::Speech::{
@@ -13,22 +14,37 @@ use crate::Result;
 use super::RecognitionPauser;
 
 mod builder;
+mod graph;
+#[cfg(any(test, feature = "test-support"))]
+mod matcher;
 mod rule;
 
 pub use builder::GrammarBuilder;
+#[cfg(feature = "test-support")]
+pub(crate) use graph::RuleGraph;
+pub use graph::{GrammarSize, OptimizationReport};
 pub use rule::{RepeatRange, Rule, RuleArena};
 
 /// A set of rules that define phrases that can be recognized.
 pub struct Grammar {
     intf: ManuallyDrop<Intf<ISpRecoGrammar>>,
     pauser: RecognitionPauser,
+    id: u64,
 }
 
 impl Grammar {
+    /// Returns the identifier of this grammar, which is unique within the process. The phrases
+    /// recognized from this grammar carry the same identifier in
+    /// [`Phrase::grammar_id`](crate::stt::Phrase::grammar_id).
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
     /// Enables or disables the recognition of all the phrases defined in this grammar.
     pub fn set_enabled(&self, enabled: bool) -> Result<()> {
         let _pause = self.pauser.pause()?;
-        unsafe { self.intf.SetGrammarState(grammar_state(enabled)) }
+        unsafe { self.intf.SetGrammarState(grammar_state(enabled)) }?;
+        Ok(())
     }
 
     /// Enables or disables the recognition of the phrases defined by the rule with the given name.
@@ -37,7 +53,8 @@ impl Grammar {
         unsafe {
             self.intf
                 .SetRuleState(name.as_ref(), null_mut(), rule_state(enabled))
-        }
+        }?;
+        Ok(())
     }
 }
 
@@ -48,6 +65,11 @@ impl Drop for Grammar {
     }
 }
 
+fn next_grammar_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
 fn grammar_state(enabled: bool) -> SPGRAMMARSTATE {
     if enabled {
         SPGS_ENABLED
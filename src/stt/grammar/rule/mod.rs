@@ -51,7 +51,7 @@ impl<'a> Rule<'a> {
 }
 
 /// Specifies the bounds for how many times the target rule in a [`Rule::Repeat`] can be repeated.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct RepeatRange {
     /// The target rule must be repeated at least this many times.
     pub min: usize,
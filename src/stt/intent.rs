@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+
+use crate::Result;
+
+use super::{Confidence, Context, EventHandler, Grammar, Phrase, Rule, RuleArena};
+
+/// A voice command that can be decoded from a recognized phrase.
+///
+/// An intent declares the named top-level rules that recognize its phrases, and converts the
+/// phrases matched by those rules into a value. The names of the rules must be unique among all
+/// the intents registered in the same [`Intents`].
+pub trait Intent: Sized {
+    /// The names of the top-level rules that recognize this intent.
+    const RULE_NAMES: &'static [&'static str];
+
+    /// Returns the definition of the top-level rule with the given name, which is one of the
+    /// [`RULE_NAMES`](Self::RULE_NAMES), allocating the rule in the given arena.
+    fn rule<'a>(name: &str, arena: &'a RuleArena<'a>) -> &'a Rule<'a>;
+
+    /// Converts the given phrase, matched by one of the rules of this intent, into the intent
+    /// value. Returns `None` if the phrase cannot be converted.
+    fn from_phrase(phrase: &Phrase) -> Option<Self>;
+}
+
+/// An intent decoded from a recognized phrase.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecognizedIntent<T> {
+    /// The decoded intent value.
+    pub intent: T,
+    /// The engine's confidence in the recognized phrase.
+    pub confidence: Confidence,
+    /// The phrase the intent was decoded from.
+    pub phrase: Phrase,
+}
+
+type RuleDefiner = for<'a> fn(&str, &'a RuleArena<'a>) -> &'a Rule<'a>;
+type PhraseDecoder<T> = fn(&Phrase) -> Option<T>;
+
+/// A registry of intents that decodes recognized phrases into values of type `T`.
+///
+/// Every registered intent must be convertible into `T`, which is usually an enum with one variant
+/// per intent. The registry builds a single grammar out of the rules of all the intents, and uses
+/// the name of the rule that matched a phrase to pick the intent to decode it into.
+pub struct Intents<T> {
+    rules: Vec<(&'static str, RuleDefiner)>,
+    decoders: HashMap<OsString, PhraseDecoder<T>>,
+}
+
+impl<T> Intents<T> {
+    /// Creates a new registry with no intents.
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            decoders: HashMap::new(),
+        }
+    }
+
+    /// Registers the given intent type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the intent declares a rule name that is already used by a registered intent.
+    pub fn register<I: Intent + Into<T>>(&mut self) -> &mut Self {
+        for name in I::RULE_NAMES {
+            if self.decoders.contains_key(OsStr::new(name)) {
+                panic!("Duplicate intent rule name: {}", name);
+            }
+            self.decoders.insert(name.into(), decode_as::<I, T>);
+            self.rules.push((*name, I::rule));
+        }
+        self
+    }
+
+    /// Builds a grammar out of the rules of all the registered intents and loads it into the given
+    /// recognition context. The newly loaded grammar must be enabled before the engine will start
+    /// recognizing the intents.
+    pub fn build_grammar(&self, context: &Context) -> Result<Grammar> {
+        let arena = RuleArena::new();
+        let mut builder = context.grammar_builder();
+        for (name, definer) in self.rules.iter() {
+            builder.add_named_rule(*name, definer(name, &arena));
+        }
+        builder.build()
+    }
+
+    /// Decodes the given phrase into an intent. Returns `None` if the phrase wasn't matched by the
+    /// rule of any registered intent, or if the intent couldn't convert it.
+    pub fn decode(&self, phrase: Phrase) -> Option<RecognizedIntent<T>> {
+        let decoder = self.decoders.get(phrase.rule.name.as_ref()?)?;
+        Some(RecognizedIntent {
+            intent: decoder(&phrase)?,
+            confidence: phrase.confidence,
+            phrase,
+        })
+    }
+
+    /// Creates an event handler for an [`EventfulContext`](super::EventfulContext) that calls the
+    /// given function with every intent decoded from a recognized phrase.
+    pub fn handler<F: Fn(RecognizedIntent<T>) + Sync>(&self, on_intent: F) -> IntentHandler<T, F> {
+        IntentHandler {
+            intents: self.clone(),
+            on_intent,
+        }
+    }
+}
+
+impl<T> Clone for Intents<T> {
+    fn clone(&self) -> Self {
+        Self {
+            rules: self.rules.clone(),
+            decoders: self.decoders.clone(),
+        }
+    }
+}
+
+fn decode_as<I: Intent + Into<T>, T>(phrase: &Phrase) -> Option<T> {
+    I::from_phrase(phrase).map(Into::into)
+}
+
+/// An event handler that decodes recognized phrases into intents. Created by
+/// [`Intents::handler`].
+pub struct IntentHandler<T, F> {
+    intents: Intents<T>,
+    on_intent: F,
+}
+
+impl<T, F: Fn(RecognizedIntent<T>) + Sync> EventHandler for IntentHandler<T, F> {
+    fn on_recognition(&self, phrase: Phrase) {
+        if let Some(intent) = self.intents.decode(phrase) {
+            (self.on_intent)(intent)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Save;
+
+    impl Intent for Save {
+        const RULE_NAMES: &'static [&'static str] = &["save"];
+
+        fn rule<'a>(_name: &str, arena: &'a RuleArena<'a>) -> &'a Rule<'a> {
+            arena.text("save")
+        }
+
+        fn from_phrase(_phrase: &Phrase) -> Option<Self> {
+            Some(Save)
+        }
+    }
+
+    struct Store;
+
+    impl Intent for Store {
+        const RULE_NAMES: &'static [&'static str] = &["store", "save"];
+
+        fn rule<'a>(name: &str, arena: &'a RuleArena<'a>) -> &'a Rule<'a> {
+            arena.text(name.to_string())
+        }
+
+        fn from_phrase(_phrase: &Phrase) -> Option<Self> {
+            Some(Store)
+        }
+    }
+
+    impl From<Save> for () {
+        fn from(_: Save) -> Self {}
+    }
+
+    impl From<Store> for () {
+        fn from(_: Store) -> Self {}
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate intent rule name: save")]
+    fn test_duplicate_rule_name() {
+        Intents::<()>::new().register::<Save>().register::<Store>();
+    }
+}
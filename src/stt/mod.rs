@@ -5,51 +5,104 @@
 //! The entry point for speech recognition is the [`Recognizer`], which encapsulates an in-process
 //! speech recognition engine. You generally won't need more than one instance of the recognizer.
 //!
+//! Alternatively, the [`SharedRecognizer`] connects to the system-wide engine, which is shared with
+//! other applications such as Windows Speech Recognition. Both recognizers support the same
+//! contexts and grammars through the common [`RecognizerBase`].
+//!
+//! The in-process recognizer uses the system's default engine, unless you select a different one.
+//! The [`installed_engines`] function allows iterating through all the installed engines, filtered
+//! by the provided criteria.
+//!
 //! ## Context
 //!
-//! The recognizer can have one or more recognition contexts. This module provides two variants of
-//! contexts:
+//! The recognizer can have one or more recognition contexts. This module provides three variants
+//! of contexts:
 //! * [`SyncContext`] will block the current thread until the engine recognizes a phrase, or until
-//! the given timeout.
+//! the given timeout. It can also iterate over the recognized phrases until a deadline, and such
+//! an iteration can be interrupted from another thread.
 //! * [`EventfulContext`] will call the supplied event handler whenever the engine recognizes a
 //! phrase.
+//! * [`ChannelContext`] will send the recognized phrases to a bounded channel, for applications
+//! that use plain threads or a runtime other than Tokio.
+//!
+//! For asynchronous recognition, see the [`tokio`](crate::tokio) module, or the
+//! [`futures`](crate::futures) module for other runtimes.
+//!
+//! A context can be configured with a minimum confidence, either for all the phrases or for the
+//! phrases of individual rules. Phrases recognized with less confidence are reported separately,
+//! as rejected [`Recognition`]s.
 //!
-//! For asynchronous recognition, see the [`tokio`](crate::tokio) module.
+//! To handle the phrases of each rule separately, use a [`Router`] as the event handler, or feed it
+//! the outcomes received from a subscriber.
+//!
+//! Voice commands modeled as Rust types can implement the [`Intent`] trait. A registry of
+//! [`Intents`] builds a single grammar for all of them, and decodes the recognized phrases into
+//! intent values.
 //!
 //! ## Grammar
 //!
 //! Each context can have one or more grammars loaded into it. A grammar consists of one or more
 //! rules that define what phrases the engine can recognize. You can enable or disable the whole
 //! grammar, or individual rules in it by their name.
+//!
+//! Large grammars can be shrunk by enabling the optimization pass in the [`GrammarBuilder`], which
+//! merges identical rules and factors out common prefixes without changing the recognized phrases
+//! or their semantic information.
 
+use std::mem::{transmute_copy, ManuallyDrop};
+use std::ops::Deref;
 use std::sync::{Arc, Mutex};
 
 use windows as Windows;
-use Windows::core::IUnknown;
+use Windows::core::{IUnknown, Interface};
+use Windows::Win32::Foundation::{BSTR, E_INVALIDARG};
 use Windows::Win32::Media::Speech::{
-    ISpRecognizer, SpInprocRecognizer, SPRECOSTATE, SPRST_ACTIVE, SPRST_INACTIVE,
+    ISpRecognizer, ISpeechRecognizer, SpInprocRecognizer, SpSharedRecognizer, SPRECOSTATE,
+    SPRST_ACTIVE, SPRST_INACTIVE,
 };
-use Windows::Win32::System::Com::{CoCreateInstance, CLSCTX_ALL};
+use Windows::Win32::System::Com::{
+    CoCreateInstance, CLSCTX_ALL, VARIANT, VARIANT_0, VARIANT_0_0, VARIANT_0_0_0,
+};
+use Windows::Win32::System::Ole::VT_BSTR;
 
-use crate::audio::AudioStream;
+use crate::audio::{default_input_device, AudioDevice, AudioDirection, AudioStream};
 use crate::com_util::Intf;
-use crate::token::Category;
+use crate::token::Token;
 use crate::Result;
 
+mod confidence;
 mod context;
+mod engine;
 mod grammar;
+mod intent;
 mod phrase;
+mod properties;
+mod router;
 mod semantics;
 
-pub use context::{Context, EventHandler, EventfulContext, SyncContext};
-pub use grammar::{Grammar, GrammarBuilder, RepeatRange, Rule, RuleArena};
-pub use phrase::Phrase;
+pub(crate) use confidence::ConfidenceFilter;
+pub use confidence::{Confidence, ConfidenceLevel, ConfidenceThreshold};
+pub use context::{
+    AudioLevel, ChannelContext, Context, ContextEvent, ContextEvents, EventHandler, EventInterest,
+    EventfulContext, Interrupter, OverflowPolicy, Recognitions, SyncContext,
+};
+pub use engine::{installed_engines, Engine, EngineSelector};
+#[cfg(feature = "test-support")]
+pub(crate) use grammar::RuleGraph;
+pub use grammar::{
+    Grammar, GrammarBuilder, GrammarSize, OptimizationReport, RepeatRange, Rule, RuleArena,
+};
+pub use intent::{Intent, IntentHandler, Intents, RecognizedIntent};
+pub use phrase::{Interference, Phrase, PhraseElement, PhraseRule, Recognition, RecognizedAudio};
+pub use router::Router;
 pub use semantics::{SemanticString, SemanticTree, SemanticValue};
 
 /// Specifies where the input for speech recognition should come from.
 pub enum RecognitionInput {
     /// Listen to the default recording device on the system
     Default,
+    /// Listen to the given recording device, which must be an input device
+    Device(AudioDevice),
     /// Read from the given stream
     Stream(AudioStream),
 }
@@ -57,21 +110,68 @@ pub enum RecognitionInput {
 impl RecognitionInput {
     fn to_sapi(self) -> Result<IUnknown> {
         Ok(match self {
-            Self::Default => {
-                Category::new(r"HKEY_LOCAL_MACHINE\SOFTWARE\Microsoft\Speech\AudioInput")?
-                    .default_token()?
-                    .to_sapi()
-                    .0
+            Self::Default => default_input_device()?.token.to_sapi().0,
+            Self::Device(device) if device.direction() != AudioDirection::Input => {
+                return Err(E_INVALIDARG.into())
             }
+            Self::Device(device) => device.token.to_sapi().0,
             Self::Stream(stream) => stream.to_sapi().0,
         })
     }
 }
 
-/// The in-process speech recognition engine.
-pub struct Recognizer {
+/// The functionality shared by the in-process [`Recognizer`] and the [`SharedRecognizer`].
+///
+/// Recognition contexts can be created for either kind of recognizer through this type.
+pub struct RecognizerBase {
     intf: Intf<ISpRecognizer>,
     pauser: RecognitionPauser,
+}
+
+impl RecognizerBase {
+    /// Makes the engine recognize the given text as if it had been spoken into the recognizer's
+    /// input. The resulting phrase is delivered to the recognition contexts in the same way as
+    /// a phrase recognized from audio, which makes this useful for driving tests and demos
+    /// deterministically.
+    ///
+    /// The words in the text must be separated by spaces. If the text doesn't match any of the
+    /// enabled rules, no phrase will be recognized.
+    pub fn emulate<S: AsRef<str>>(&self, text: S) -> Result<()> {
+        let intf: ISpeechRecognizer = self.intf.cast()?;
+        let text = BSTR::from(text.as_ref());
+        // The variant only borrows the string, which is freed when `text` is dropped
+        let text_elements = VARIANT {
+            Anonymous: VARIANT_0 {
+                Anonymous: ManuallyDrop::new(VARIANT_0_0 {
+                    vt: VT_BSTR.0 as _,
+                    Anonymous: VARIANT_0_0_0 {
+                        bstrVal: ManuallyDrop::new(unsafe { transmute_copy(&text) }),
+                    },
+                    ..Default::default()
+                }),
+            },
+        };
+        let display_attrs = VARIANT::default();
+        unsafe { intf.EmulateRecognition(&text_elements, &display_attrs, 0) }?;
+        Ok(())
+    }
+
+    /// Returns the engine the recognizer is currently using.
+    pub fn engine(&self) -> Result<Engine> {
+        let intf = unsafe { self.intf.GetRecognizer() }?;
+        Ok(Engine {
+            token: Token::from_sapi(intf),
+        })
+    }
+}
+
+/// The in-process speech recognition engine.
+///
+/// The in-process engine is owned exclusively by the application, which chooses its input and its
+/// engine, and can tune how eagerly the engine concludes and accepts phrases. All the methods of
+/// [`RecognizerBase`] are available through deref.
+pub struct Recognizer {
+    base: RecognizerBase,
     global_pause: Mutex<Option<ScopedPause>>,
 }
 
@@ -81,16 +181,41 @@ impl Recognizer {
         let intf: ISpRecognizer =
             unsafe { CoCreateInstance(&SpInprocRecognizer, None, CLSCTX_ALL) }?;
         unsafe { intf.SetInput(RecognitionInput::Default.to_sapi()?, false) }?;
-        Ok(Self {
-            pauser: RecognitionPauser::new(intf.clone()),
-            intf: Intf(intf),
+        Ok(Self::from_sapi(intf))
+    }
+
+    /// Creates a new recognition engine of the given kind, configured to listen to the default
+    /// recording device.
+    pub fn with_engine(engine: &Engine) -> Result<Self> {
+        let intf: ISpRecognizer =
+            unsafe { CoCreateInstance(&SpInprocRecognizer, None, CLSCTX_ALL) }?;
+        unsafe { intf.SetRecognizer(&engine.token) }?;
+        unsafe { intf.SetInput(RecognitionInput::Default.to_sapi()?, false) }?;
+        Ok(Self::from_sapi(intf))
+    }
+
+    fn from_sapi(intf: ISpRecognizer) -> Self {
+        Self {
+            base: RecognizerBase {
+                pauser: RecognitionPauser::new(intf.clone()),
+                intf: Intf(intf),
+            },
             global_pause: Mutex::new(None),
-        })
+        }
+    }
+
+    /// Switches the recognizer to the given engine. The engine should be switched before any
+    /// recognition contexts are created, because the engine may refuse to be switched while it
+    /// has grammars loaded.
+    pub fn set_engine(&self, engine: &Engine) -> Result<()> {
+        unsafe { self.intf.SetRecognizer(&engine.token) }?;
+        Ok(())
     }
 
     /// Configures the recognizer to listen to the given input.
     pub fn set_input(&self, input: RecognitionInput, allow_fmt_changes: bool) -> Result<()> {
-        unsafe { self.intf.SetInput(input.to_sapi()?, allow_fmt_changes) }
+        unsafe { self.intf.SetInput(input.to_sapi()?, allow_fmt_changes) }?;
+        Ok(())
     }
 
     /// Enables or disables recognition.
@@ -107,6 +232,53 @@ impl Recognizer {
     }
 }
 
+impl Deref for Recognizer {
+    type Target = RecognizerBase;
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+/// The system-wide speech recognition engine, shared with other applications such as Windows
+/// Speech Recognition.
+///
+/// The shared engine listens to the input and uses the engine configured by the user in the
+/// system settings, so this type doesn't allow changing them. Its recognition state is shared by
+/// all the applications using it. To stop recognizing phrases in this application only, disable
+/// its recognition contexts instead. All the methods of [`RecognizerBase`] are available through
+/// deref.
+pub struct SharedRecognizer {
+    base: RecognizerBase,
+}
+
+impl SharedRecognizer {
+    /// Connects to the shared recognition engine, starting it if necessary.
+    pub fn new() -> Result<Self> {
+        let intf: ISpRecognizer =
+            unsafe { CoCreateInstance(&SpSharedRecognizer, None, CLSCTX_ALL) }?;
+        Ok(Self {
+            base: RecognizerBase {
+                intf: Intf(intf),
+                pauser: RecognitionPauser::shared(),
+            },
+        })
+    }
+
+    /// Enables or disables recognition system-wide. This affects every application using the
+    /// shared engine.
+    pub fn set_system_enabled(&self, enabled: bool) -> Result<()> {
+        unsafe { self.intf.SetRecoState(reco_state(enabled)) }?;
+        Ok(())
+    }
+}
+
+impl Deref for SharedRecognizer {
+    type Target = RecognizerBase;
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
 fn reco_state(enabled: bool) -> SPRECOSTATE {
     if enabled {
         SPRST_ACTIVE
@@ -140,31 +312,37 @@ impl PauserState {
 
 #[derive(Clone)]
 struct RecognitionPauser {
-    state: Arc<Mutex<PauserState>>,
+    state: Option<Arc<Mutex<PauserState>>>,
 }
 
 impl RecognitionPauser {
     fn new(intf: ISpRecognizer) -> Self {
         Self {
-            state: Arc::new(Mutex::new(PauserState {
+            state: Some(Arc::new(Mutex::new(PauserState {
                 intf: Intf(intf),
                 pause_count: 0,
-            })),
+            }))),
         }
     }
 
+    // The shared engine synchronizes grammar changes by itself, and pausing it would also pause
+    // the recognition for the other applications
+    fn shared() -> Self {
+        Self { state: None }
+    }
+
     fn pause(&self) -> Result<ScopedPause> {
         ScopedPause::new(self.state.clone())
     }
 }
 
 struct ScopedPause {
-    state: Arc<Mutex<PauserState>>,
+    state: Option<Arc<Mutex<PauserState>>>,
 }
 
 impl ScopedPause {
-    fn new(state: Arc<Mutex<PauserState>>) -> Result<Self> {
-        {
+    fn new(state: Option<Arc<Mutex<PauserState>>>) -> Result<Self> {
+        if let Some(state) = state.as_ref() {
             state.lock().unwrap().pause()?;
         }
         Ok(Self { state })
@@ -173,7 +351,9 @@ impl ScopedPause {
 
 impl Drop for ScopedPause {
     fn drop(&mut self) {
-        // The following call is expected to succeed, but failure shouldn't cause panic
-        let _ = self.state.lock().unwrap().resume();
+        if let Some(state) = self.state.as_ref() {
+            // The following call is expected to succeed, but failure shouldn't cause panic
+            let _ = state.lock().unwrap().resume();
+        }
     }
 }
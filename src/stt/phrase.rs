@@ -1,13 +1,30 @@
 use std::ffi::OsString;
+use std::io::{self, Write};
+use std::mem::MaybeUninit;
+use std::ops::Range;
+use std::path::Path;
 use std::ptr::null_mut;
+use std::time::Duration;
 
 use windows as Windows;
-use Windows::Win32::Media::Speech::{ISpRecoResult, SPPHRASE_50, SPPR_ALL_ELEMENTS};
+use Windows::core::GUID;
+use Windows::Win32::Foundation::{E_FAIL, E_POINTER};
+use Windows::Win32::Media::Audio::WAVEFORMATEX;
+use Windows::Win32::Media::Speech::{
+    ISpPhrase, ISpPhraseAlt, ISpRecoContext, ISpRecoResult, SPAO_RETAIN_AUDIO, SPAUDIOOPTIONS,
+    SPINTERFERENCE, SPINTERFERENCE_LATENCY_TRUNCATE_BEGIN, SPINTERFERENCE_LATENCY_TRUNCATE_END,
+    SPINTERFERENCE_LATENCY_WARNING, SPINTERFERENCE_NOISE, SPINTERFERENCE_NOSIGNAL,
+    SPINTERFERENCE_TOOFAST, SPINTERFERENCE_TOOLOUD, SPINTERFERENCE_TOOQUIET,
+    SPINTERFERENCE_TOOSLOW, SPPHRASEELEMENT, SPPHRASERULE, SPPHRASE_50, SPPR_ALL_ELEMENTS,
+    SPRECORESULTTIMES,
+};
 
-use crate::com_util::{from_wide, out_to_ret, ComBox};
+use crate::audio::{save_wav, write_wav, AudioFormat, AudioStream};
+use crate::com_util::{from_wide, opt_from_wide, out_to_ret, ComBox};
+use crate::error::SPERR_UNSUPPORTED_FORMAT;
 use crate::Result;
 
-use super::SemanticTree;
+use super::{Confidence, SemanticTree};
 
 /// A successfully recognized phrase.
 #[derive(Debug, PartialEq, Clone)]
@@ -16,14 +33,291 @@ pub struct Phrase {
     pub text: OsString,
     /// The semantic information associated with the phrase.
     pub semantics: Vec<SemanticTree>,
+    /// The top-level rule that matched the phrase, along with the sub-rules that matched its
+    /// parts.
+    pub rule: PhraseRule,
+    /// The identifier of the grammar that contains the matched rule. See
+    /// [`Grammar::id`](crate::stt::Grammar::id).
+    pub grammar_id: u64,
+    /// The engine's confidence in the phrase as a whole.
+    pub confidence: Confidence,
+    /// The individual words that make up the phrase.
+    pub elements: Vec<PhraseElement>,
+    /// The position in the audio stream where the phrase starts.
+    pub offset: Duration,
+    /// The length of the audio for the whole phrase.
+    pub duration: Duration,
+    /// The audio the engine recognized the phrase from, if the context was configured to retain
+    /// it with [`Context::set_retain_audio`](crate::stt::Context::set_retain_audio) and the engine
+    /// was able to provide it.
+    pub audio: Option<RecognizedAudio>,
+    /// The alternative interpretations of the utterance, ordered from the most to the least likely.
+    /// The number of alternates is limited by
+    /// [`Context::set_max_alternates`](crate::stt::Context::set_max_alternates). Alternates don't
+    /// have alternates of their own.
+    pub alternates: Vec<Phrase>,
+}
+
+/// A rule that matched a part of a recognized phrase.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PhraseRule {
+    /// The name of the rule, if it has one.
+    pub name: Option<OsString>,
+    /// The identifier the engine assigned to the rule.
+    pub id: u32,
+    /// The indices of the words in [`Phrase::elements`] that this rule matched.
+    pub elements: Range<usize>,
+    /// The engine's confidence in the match.
+    pub confidence: Confidence,
+    /// The sub-rules that matched parts of the words this rule matched.
+    pub children: Vec<PhraseRule>,
+}
+
+impl PhraseRule {
+    fn from_sapi(sapi_rule: &SPPHRASERULE) -> Self {
+        let first = sapi_rule.ulFirstElement as usize;
+        let mut children = Vec::new();
+        let mut next_child = unsafe { sapi_rule.pFirstChild.as_ref() };
+        while let Some(child) = next_child {
+            children.push(Self::from_sapi(child));
+            next_child = unsafe { child.pNextSibling.as_ref() };
+        }
+        Self {
+            name: unsafe { opt_from_wide(&sapi_rule.pszName) },
+            id: sapi_rule.ulId,
+            elements: first..(first + sapi_rule.ulCountOfElements as usize),
+            confidence: Confidence::from_sapi(sapi_rule.Confidence, sapi_rule.SREngineConfidence),
+            children,
+        }
+    }
+}
+
+/// A single word in a recognized phrase.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PhraseElement {
+    /// The text of the word, as it should be displayed.
+    pub text: OsString,
+    /// The lexical form of the word, as it was spoken.
+    pub lexical_form: OsString,
+    /// The position in the audio stream where the word starts.
+    pub offset: Duration,
+    /// The length of the audio for the word.
+    pub duration: Duration,
+    /// The engine's confidence in the word.
+    pub confidence: Confidence,
+}
+
+/// The audio the engine recognized a phrase from.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RecognizedAudio {
+    /// The format of the audio.
+    pub format: AudioFormat,
+    /// The PCM audio data.
+    pub data: Vec<u8>,
+}
+
+impl RecognizedAudio {
+    /// Writes the audio into the writer as a WAV file.
+    pub fn write_wav<W: Write>(&self, writer: W) -> io::Result<()> {
+        write_wav(writer, &self.format, &self.data)
+    }
+
+    /// Creates a WAV file at the given path and writes the audio into it.
+    pub fn save_wav<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        save_wav(path, &self.format, &self.data)
+    }
+
+    /// Writes the audio data into the given stream. The stream should have the same format as the
+    /// audio.
+    pub fn write_to(&self, stream: &AudioStream) -> Result<()> {
+        let sapi_stream = stream.to_sapi();
+        let mut remaining = &self.data[..];
+        while !remaining.is_empty() {
+            let len = remaining.len().min(u32::MAX as usize);
+            let written =
+                unsafe { sapi_stream.Write(remaining.as_ptr() as _, len as u32) }? as usize;
+            if written == 0 {
+                return Err(E_FAIL.into());
+            }
+            remaining = &remaining[written..];
+        }
+        Ok(())
+    }
+
+    fn from_sapi(sapi_result: &ISpRecoResult) -> Result<Self> {
+        let sapi_stream = unsafe { sapi_result.GetAudio(0, SPPR_ALL_ELEMENTS.0 as u32) }?;
+        let mut format_id = GUID::zeroed();
+        let sapi_fmt = unsafe {
+            ComBox::from_raw(sapi_stream.GetFormat(&mut format_id)? as *const WAVEFORMATEX)
+        };
+        let format = unsafe { sapi_fmt.as_ref() }
+            .and_then(AudioFormat::from_sapi)
+            .ok_or(SPERR_UNSUPPORTED_FORMAT)?;
+
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let read = unsafe {
+                out_to_ret(|out| sapi_stream.Read(buf.as_mut_ptr() as _, buf.len() as u32, out))
+            }?;
+            if read == 0 {
+                break;
+            }
+            data.extend_from_slice(&buf[..read as usize]);
+        }
+        Ok(Self { format, data })
+    }
+}
+
+/// The outcome of a recognition, after the phrase was checked against the minimum confidence
+/// configured in the context.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Recognition {
+    /// The phrase met the minimum confidence.
+    Accepted(Phrase),
+    /// The phrase was recognized, but with less than the minimum confidence.
+    Rejected(Phrase),
+    /// The engine heard speech that didn't match any of the enabled rules. Holds the partial
+    /// phrase the engine was able to recognize, if any. Only reported if false recognitions are
+    /// enabled with [`Context::set_interest`](crate::stt::Context::set_interest).
+    FalseRecognition(Option<Phrase>),
+    /// The engine detected a problem with the audio that interferes with the recognition. Only
+    /// reported if interference is enabled with
+    /// [`Context::set_interest`](crate::stt::Context::set_interest).
+    Interference(Interference),
+}
+
+impl Recognition {
+    /// Returns the phrase, if it was accepted.
+    pub fn accepted(self) -> Option<Phrase> {
+        match self {
+            Self::Accepted(phrase) => Some(phrase),
+            _ => None,
+        }
+    }
+}
+
+/// A problem with the audio that interferes with the recognition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Interference {
+    /// There is too much background noise.
+    Noise,
+    /// The engine isn't receiving any audio.
+    NoSignal,
+    /// The user is speaking too loud.
+    TooLoud,
+    /// The user is speaking too quietly.
+    TooQuiet,
+    /// The user is speaking too fast.
+    TooFast,
+    /// The user is speaking too slowly.
+    TooSlow,
+    /// The engine is falling behind the audio.
+    LatencyWarning,
+    /// The engine fell behind and dropped audio at the beginning of the utterance.
+    LatencyTruncateBegin,
+    /// The engine fell behind and dropped audio at the end of the utterance.
+    LatencyTruncateEnd,
+}
+
+impl Interference {
+    pub(crate) fn from_sapi(sapi_interference: SPINTERFERENCE) -> Option<Self> {
+        match sapi_interference {
+            SPINTERFERENCE_NOISE => Some(Self::Noise),
+            SPINTERFERENCE_NOSIGNAL => Some(Self::NoSignal),
+            SPINTERFERENCE_TOOLOUD => Some(Self::TooLoud),
+            SPINTERFERENCE_TOOQUIET => Some(Self::TooQuiet),
+            SPINTERFERENCE_TOOFAST => Some(Self::TooFast),
+            SPINTERFERENCE_TOOSLOW => Some(Self::TooSlow),
+            SPINTERFERENCE_LATENCY_WARNING => Some(Self::LatencyWarning),
+            SPINTERFERENCE_LATENCY_TRUNCATE_BEGIN => Some(Self::LatencyTruncateBegin),
+            SPINTERFERENCE_LATENCY_TRUNCATE_END => Some(Self::LatencyTruncateEnd),
+            _ => None,
+        }
+    }
 }
 
 impl Phrase {
     // Note: must be a recognized phrase, not a hypothesis or a false recognition
     pub(crate) fn from_sapi(sapi_result: ISpRecoResult) -> Result<Self> {
+        let (offset, duration) = Self::result_times(&sapi_result)?;
+        let sapi_ctx = unsafe { sapi_result.GetRecoContext() }?;
+        let mut phrase = Self::from_sapi_phrase(&sapi_result.clone().into(), offset, duration)?;
+        // Alternates are a best effort: the engine might not support them (SPERR_NOT_FOUND) or
+        // fail to produce them, but the recognized phrase is still valid
+        phrase.alternates =
+            Self::alternates(&sapi_result, &sapi_ctx, offset, duration).unwrap_or_default();
+        // Like the alternates, the audio is not worth losing the recognized phrase over
+        if is_audio_retained(&sapi_ctx).unwrap_or(false) {
+            phrase.audio = RecognizedAudio::from_sapi(&sapi_result).ok();
+        }
+        Ok(phrase)
+    }
+
+    // Decodes a hypothesis or a false recognition, neither of which has alternates
+    pub(crate) fn from_sapi_partial(sapi_result: ISpRecoResult) -> Result<Self> {
+        let (offset, duration) = Self::result_times(&sapi_result)?;
+        Self::from_sapi_phrase(&sapi_result.into(), offset, duration)
+    }
+
+    // A false recognition might not contain any words at all
+    pub(crate) fn from_sapi_false(sapi_result: ISpRecoResult) -> Result<Option<Self>> {
+        let phrase = Self::from_sapi_partial(sapi_result)?;
+        Ok(if phrase.elements.is_empty() {
+            None
+        } else {
+            Some(phrase)
+        })
+    }
+
+    fn result_times(sapi_result: &ISpRecoResult) -> Result<(Duration, Duration)> {
+        let times = unsafe {
+            let mut times = MaybeUninit::<SPRECORESULTTIMES>::uninit();
+            sapi_result.GetResultTimes(times.as_mut_ptr())?;
+            times.assume_init()
+        };
+        Ok((from_hns(times.ullStart), from_hns(times.ullLength)))
+    }
+
+    fn alternates(
+        sapi_result: &ISpRecoResult,
+        sapi_ctx: &ISpRecoContext,
+        offset: Duration,
+        duration: Duration,
+    ) -> Result<Vec<Self>> {
+        let max_alternates = unsafe { out_to_ret(|out| sapi_ctx.GetMaxAlternates(out)) }?;
+        if max_alternates == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut sapi_alts: Vec<Option<ISpPhraseAlt>> = vec![None; max_alternates as usize];
+        let count = unsafe {
+            out_to_ret(|out| {
+                sapi_result.GetAlternates(
+                    0,
+                    SPPR_ALL_ELEMENTS.0 as u32,
+                    max_alternates,
+                    sapi_alts.as_mut_ptr(),
+                    out,
+                )
+            })
+        }?;
+        sapi_alts
+            .into_iter()
+            .take(count as usize)
+            .flatten()
+            .map(|sapi_alt| Self::from_sapi_phrase(&sapi_alt.into(), offset, duration))
+            .collect()
+    }
+
+    fn from_sapi_phrase(
+        sapi_phrase: &ISpPhrase,
+        offset: Duration,
+        duration: Duration,
+    ) -> Result<Self> {
         let text = unsafe {
             ComBox::from_raw(out_to_ret(|out| {
-                sapi_result.GetText(
+                sapi_phrase.GetText(
                     SPPR_ALL_ELEMENTS.0 as u32,
                     SPPR_ALL_ELEMENTS.0 as u32,
                     true,
@@ -33,12 +327,59 @@ impl Phrase {
             })?)
         };
         let phrase_info =
-            unsafe { ComBox::from_raw(sapi_result.GetPhrase()? as *const SPPHRASE_50) };
-        let first_prop = unsafe { (*phrase_info).as_ref() }
-            .and_then(|info| unsafe { info.pProperties.as_ref() });
+            unsafe { ComBox::from_raw(sapi_phrase.GetPhrase()? as *const SPPHRASE_50) };
+        let info = unsafe { (*phrase_info).as_ref() }.ok_or(E_POINTER)?;
+        let first_prop = unsafe { info.pProperties.as_ref() };
+        let elements = if info.pElements.is_null() {
+            &[]
+        } else {
+            unsafe {
+                std::slice::from_raw_parts(info.pElements, info.Rule.ulCountOfElements as usize)
+            }
+        };
         Ok(Self {
             text: unsafe { from_wide(&text) },
             semantics: SemanticTree::from_sapi(first_prop),
+            rule: PhraseRule::from_sapi(&info.Rule),
+            grammar_id: info.ullGrammarID,
+            confidence: Confidence::from_sapi(info.Rule.Confidence, info.Rule.SREngineConfidence),
+            elements: elements
+                .iter()
+                .map(|elem| PhraseElement::from_sapi(elem, offset))
+                .collect(),
+            offset,
+            duration,
+            audio: None,
+            alternates: Vec::new(),
         })
     }
 }
+
+impl PhraseElement {
+    fn from_sapi(sapi_elem: &SPPHRASEELEMENT, phrase_offset: Duration) -> Self {
+        Self {
+            text: unsafe { opt_from_wide(&sapi_elem.pszDisplayText) }.unwrap_or_default(),
+            lexical_form: unsafe { opt_from_wide(&sapi_elem.pszLexicalForm) }.unwrap_or_default(),
+            offset: phrase_offset + from_hns(sapi_elem.ulAudioTimeOffset as _),
+            duration: from_hns(sapi_elem.ulAudioSizeTime as _),
+            confidence: Confidence::from_sapi(
+                sapi_elem.ActualConfidence,
+                sapi_elem.SREngineConfidence,
+            ),
+        }
+    }
+}
+
+fn is_audio_retained(sapi_ctx: &ISpRecoContext) -> Result<bool> {
+    let mut options = SPAUDIOOPTIONS::default();
+    let mut format_id = GUID::zeroed();
+    let mut sapi_fmt: *mut WAVEFORMATEX = null_mut();
+    unsafe { sapi_ctx.GetAudioOptions(&mut options, &mut format_id, &mut sapi_fmt) }?;
+    drop(unsafe { ComBox::from_raw(sapi_fmt as *const WAVEFORMATEX) });
+    Ok(options == SPAO_RETAIN_AUDIO)
+}
+
+// SAPI measures time in units of 100 nanoseconds
+fn from_hns(units: u64) -> Duration {
+    Duration::from_nanos(units * 100)
+}
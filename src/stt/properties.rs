@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use windows as Windows;
+use Windows::Win32::Foundation::E_INVALIDARG;
+
+use crate::Result;
+
+use super::Recognizer;
+
+const RESPONSE_SPEED: &str = "ResponseSpeed";
+const COMPLEX_RESPONSE_SPEED: &str = "ComplexResponseSpeed";
+const HIGH_CONFIDENCE_THRESHOLD: &str = "HighConfidenceThreshold";
+const NORMAL_CONFIDENCE_THRESHOLD: &str = "NormalConfidenceThreshold";
+const LOW_CONFIDENCE_THRESHOLD: &str = "LowConfidenceThreshold";
+const REJECTION_THRESHOLD: &str = "CFGConfidenceRejectionThreshold";
+const ADAPTATION_ON: &str = "AdaptationOn";
+
+const MAX_RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_THRESHOLD: u8 = 100;
+
+// Tuning properties of the engine. Engines ignore the properties they don't support, and report
+// zero as their value.
+impl Recognizer {
+    /// Returns how long the engine waits after the speech stops before it concludes a phrase that
+    /// fully matches a rule.
+    pub fn response_timeout(&self) -> Result<Duration> {
+        self.duration_property(RESPONSE_SPEED)
+    }
+
+    /// Sets how long the engine waits after the speech stops before it concludes a phrase that
+    /// fully matches a rule. The timeout cannot be longer than 10 seconds.
+    pub fn set_response_timeout(&self, timeout: Duration) -> Result<()> {
+        self.set_duration_property(RESPONSE_SPEED, timeout)
+    }
+
+    /// Returns how long the engine waits after the speech stops before it concludes a phrase that
+    /// could be continued to match a rule, i.e. one that only matches a prefix of a rule.
+    pub fn incomplete_response_timeout(&self) -> Result<Duration> {
+        self.duration_property(COMPLEX_RESPONSE_SPEED)
+    }
+
+    /// Sets how long the engine waits after the speech stops before it concludes a phrase that
+    /// could be continued to match a rule. The timeout cannot be longer than 10 seconds.
+    pub fn set_incomplete_response_timeout(&self, timeout: Duration) -> Result<()> {
+        self.set_duration_property(COMPLEX_RESPONSE_SPEED, timeout)
+    }
+
+    /// Returns the score, between 0 and 100, above which the engine reports a high confidence.
+    pub fn high_confidence_threshold(&self) -> Result<u8> {
+        self.threshold_property(HIGH_CONFIDENCE_THRESHOLD)
+    }
+
+    /// Sets the score, between 0 and 100, above which the engine reports a high confidence.
+    pub fn set_high_confidence_threshold(&self, threshold: u8) -> Result<()> {
+        self.set_threshold_property(HIGH_CONFIDENCE_THRESHOLD, threshold)
+    }
+
+    /// Returns the score, between 0 and 100, above which the engine reports a normal confidence.
+    pub fn normal_confidence_threshold(&self) -> Result<u8> {
+        self.threshold_property(NORMAL_CONFIDENCE_THRESHOLD)
+    }
+
+    /// Sets the score, between 0 and 100, above which the engine reports a normal confidence.
+    pub fn set_normal_confidence_threshold(&self, threshold: u8) -> Result<()> {
+        self.set_threshold_property(NORMAL_CONFIDENCE_THRESHOLD, threshold)
+    }
+
+    /// Returns the score, between 0 and 100, below which the engine reports a low confidence.
+    pub fn low_confidence_threshold(&self) -> Result<u8> {
+        self.threshold_property(LOW_CONFIDENCE_THRESHOLD)
+    }
+
+    /// Sets the score, between 0 and 100, below which the engine reports a low confidence.
+    pub fn set_low_confidence_threshold(&self, threshold: u8) -> Result<()> {
+        self.set_threshold_property(LOW_CONFIDENCE_THRESHOLD, threshold)
+    }
+
+    /// Returns the score, between 0 and 100, below which the engine itself rejects a phrase and
+    /// reports it as a false recognition.
+    pub fn rejection_threshold(&self) -> Result<u8> {
+        self.threshold_property(REJECTION_THRESHOLD)
+    }
+
+    /// Sets the score, between 0 and 100, below which the engine itself rejects a phrase and
+    /// reports it as a false recognition.
+    pub fn set_rejection_threshold(&self, threshold: u8) -> Result<()> {
+        self.set_threshold_property(REJECTION_THRESHOLD, threshold)
+    }
+
+    /// Returns `true` if the engine adapts its acoustic model to the speaker in the background.
+    pub fn adaptation_enabled(&self) -> Result<bool> {
+        self.num_property(ADAPTATION_ON).map(|value| value != 0)
+    }
+
+    /// Enables or disables the background adaptation of the acoustic model to the speaker.
+    pub fn set_adaptation_enabled(&self, enabled: bool) -> Result<()> {
+        self.set_num_property(ADAPTATION_ON, enabled as i32)
+    }
+
+    fn duration_property(&self, name: &str) -> Result<Duration> {
+        let millis = self.num_property(name)?;
+        Ok(Duration::from_millis(millis.max(0) as u64))
+    }
+
+    fn set_duration_property(&self, name: &str, value: Duration) -> Result<()> {
+        if value > MAX_RESPONSE_TIMEOUT {
+            return Err(E_INVALIDARG.into());
+        }
+        self.set_num_property(name, value.as_millis() as i32)
+    }
+
+    fn threshold_property(&self, name: &str) -> Result<u8> {
+        let value = self.num_property(name)?;
+        Ok(value.clamp(0, MAX_THRESHOLD as i32) as u8)
+    }
+
+    fn set_threshold_property(&self, name: &str, value: u8) -> Result<()> {
+        if value > MAX_THRESHOLD {
+            return Err(E_INVALIDARG.into());
+        }
+        self.set_num_property(name, value as i32)
+    }
+
+    fn num_property(&self, name: &str) -> Result<i32> {
+        // The engine leaves the value untouched if it doesn't support the property
+        let mut value = 0;
+        unsafe { self.intf.GetPropertyNum(name, &mut value) }?;
+        Ok(value)
+    }
+
+    fn set_num_property(&self, name: &str, value: i32) -> Result<()> {
+        unsafe { self.intf.SetPropertyNum(name, value) }?;
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+use std::ffi::OsString;
+
+use super::{EventHandler, Interference, Phrase, Recognition};
+
+type PhraseHandler = Box<dyn Fn(Phrase) + Send + Sync>;
+type PhrasePredicate = Box<dyn Fn(&Phrase) -> bool + Send + Sync>;
+type FallbackHandler = Box<dyn Fn(Recognition) + Send + Sync>;
+
+struct Route {
+    rule: OsString,
+    predicate: Option<PhrasePredicate>,
+    handler: PhraseHandler,
+}
+
+/// Dispatches recognized phrases to handlers based on the name of the top-level rule that matched
+/// them.
+///
+/// The routes are tried in the order they were added, and the phrase is passed to the first route
+/// whose rule name and predicate match it. Rejected phrases, false recognitions, interference, and
+/// phrases that don't match any route are passed to the fallback handler, if there is one.
+///
+/// A router can be used as the handler for an [`EventfulContext`](super::EventfulContext), or it
+/// can be fed with outcomes awaited from a subscriber by calling [`dispatch`](Self::dispatch).
+pub struct Router {
+    routes: Vec<Route>,
+    fallback: Option<FallbackHandler>,
+}
+
+impl Router {
+    /// Creates a new router with no routes and no fallback.
+    pub fn new() -> Self {
+        Self {
+            routes: Vec::new(),
+            fallback: None,
+        }
+    }
+
+    /// Adds a route that passes the phrases matched by the rule with the given name to the given
+    /// handler.
+    pub fn route<S, F>(&mut self, rule: S, handler: F) -> &mut Self
+    where
+        S: Into<OsString>,
+        F: Fn(Phrase) + Send + Sync + 'static,
+    {
+        self.routes.push(Route {
+            rule: rule.into(),
+            predicate: None,
+            handler: Box::new(handler),
+        });
+        self
+    }
+
+    /// Adds a route that passes the phrases matched by the rule with the given name to the given
+    /// handler, but only if they also satisfy the given predicate, e.g. if they carry a specific
+    /// semantic value.
+    pub fn route_if<S, P, F>(&mut self, rule: S, predicate: P, handler: F) -> &mut Self
+    where
+        S: Into<OsString>,
+        P: Fn(&Phrase) -> bool + Send + Sync + 'static,
+        F: Fn(Phrase) + Send + Sync + 'static,
+    {
+        self.routes.push(Route {
+            rule: rule.into(),
+            predicate: Some(Box::new(predicate)),
+            handler: Box::new(handler),
+        });
+        self
+    }
+
+    /// Sets the handler that will receive the outcomes that aren't passed to any of the routes.
+    pub fn fallback<F: Fn(Recognition) + Send + Sync + 'static>(
+        &mut self,
+        handler: F,
+    ) -> &mut Self {
+        self.fallback = Some(Box::new(handler));
+        self
+    }
+
+    /// Passes the given outcome to the matching route, or to the fallback handler.
+    pub fn dispatch(&self, recognition: Recognition) {
+        let recognition = match recognition {
+            Recognition::Accepted(phrase) => match self.find_route(&phrase) {
+                Some(route) => return (route.handler)(phrase),
+                None => Recognition::Accepted(phrase),
+            },
+            recognition => recognition,
+        };
+        if let Some(fallback) = self.fallback.as_ref() {
+            fallback(recognition);
+        }
+    }
+
+    fn find_route(&self, phrase: &Phrase) -> Option<&Route> {
+        let name = phrase.rule.name.as_ref()?;
+        self.routes.iter().find(|route| {
+            route.rule == *name && route.predicate.as_ref().map_or(true, |pred| pred(phrase))
+        })
+    }
+}
+
+impl EventHandler for Router {
+    fn on_recognition(&self, phrase: Phrase) {
+        self.dispatch(Recognition::Accepted(phrase))
+    }
+
+    fn on_rejection(&self, phrase: Phrase) {
+        self.dispatch(Recognition::Rejected(phrase))
+    }
+
+    fn on_false_recognition(&self, phrase: Option<Phrase>) {
+        self.dispatch(Recognition::FalseRecognition(phrase))
+    }
+
+    fn on_interference(&self, interference: Interference) {
+        self.dispatch(Recognition::Interference(interference))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn test_interference_fallback() {
+        let (tx, rx) = mpsc::sync_channel(1);
+        let mut router = Router::new();
+        router.route("save", |_| panic!("unexpected route"));
+        router.fallback(move |outcome| tx.send(outcome).unwrap());
+
+        router.on_interference(Interference::TooLoud);
+        assert_eq!(
+            Recognition::Interference(Interference::TooLoud),
+            rx.try_recv().unwrap()
+        );
+    }
+}
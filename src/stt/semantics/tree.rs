@@ -4,15 +4,21 @@ use std::ops::{Deref, DerefMut};
 use windows as Windows;
 use Windows::Win32::Media::Speech::SPPHRASEPROPERTY;
 
+use crate::stt::Confidence;
+
 use super::SemanticValue;
 
 /// A tree of values that forms part of the semantic information for a recognized phrase.
-#[derive(Debug, PartialEq, Clone)]
+///
+/// Two trees are equal if their values and sub-trees are equal. The confidence is not compared.
+#[derive(Debug, Clone)]
 pub struct SemanticTree {
     /// The value at the root of this tree.
     pub value: SemanticValue<OsString>,
     /// The sub-trees that form this tree.
     pub children: Vec<SemanticTree>,
+    /// The engine's confidence in the value, or `None` if the tree wasn't produced by the engine.
+    pub confidence: Option<Confidence>,
 }
 
 impl SemanticTree {
@@ -24,6 +30,10 @@ impl SemanticTree {
                 result.push(SemanticTree {
                     value,
                     children: SemanticTree::from_sapi(unsafe { prop.pFirstChild.as_ref() }),
+                    confidence: Some(Confidence::from_sapi(
+                        prop.Confidence,
+                        prop.SREngineConfidence,
+                    )),
                 });
             }
             next_prop = unsafe { prop.pNextSibling.as_ref() };
@@ -32,6 +42,12 @@ impl SemanticTree {
     }
 }
 
+impl PartialEq for SemanticTree {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.children == other.children
+    }
+}
+
 impl Deref for SemanticTree {
     type Target = Vec<SemanticTree>;
 
@@ -0,0 +1,326 @@
+//! Support for testing speech recognition grammars.
+//!
+//! A [`GrammarTest`] pairs a set of top-level rules with a table of utterances the grammar must
+//! accept, along with the semantic information they must produce, and utterances the grammar must
+//! reject. The utterances are matched against the rules in pure Rust, so the tests don't depend on
+//! the installed recognition engine, the audio devices, or the timing of the recognition.
+//! Alternatively, [`GrammarTest::run_emulated`] builds the grammar in a real recognizer and feeds it
+//! the utterances through [`RecognizerBase::emulate`].
+//!
+//! ```
+//! use sapi_lite::stt::Rule;
+//! use sapi_lite::testing::{leaf, GrammarTest};
+//!
+//! let salad = Rule::text("salad");
+//! let item = Rule::semantic(6, &salad);
+//! let arthur = Rule::text("Arthur");
+//! let guest = Rule::semantic("Arthur", &arthur);
+//! let serve = Rule::text("serve");
+//! let to = Rule::text("to");
+//! let rule = Rule::sequence(vec![&serve, &item, &to, &guest]);
+//!
+//! GrammarTest::new()
+//!     .add_named_rule("serve", &rule)
+//!     .accept("serve salad to Arthur", vec![leaf(6), leaf("Arthur")])
+//!     .reject("serve to Arthur")
+//!     .assert();
+//! ```
+
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+use crate::stt::{RecognizerBase, Rule, RuleGraph, SemanticTree, SemanticValue, SyncContext};
+
+/// A table of utterances that a grammar must accept or reject.
+pub struct GrammarTest<'a> {
+    rules: Vec<(&'a Rule<'a>, Option<Cow<'a, str>>)>,
+    cases: Vec<TestCase>,
+    optimized: bool,
+}
+
+struct TestCase {
+    utterance: String,
+    expected: Option<Vec<SemanticTree>>,
+}
+
+impl<'a> GrammarTest<'a> {
+    /// Creates a new test with no rules and no utterances.
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            cases: Vec::new(),
+            optimized: false,
+        }
+    }
+
+    /// Adds an unnamed top-level rule to the tested grammar.
+    pub fn add_rule(&mut self, rule: &'a Rule<'a>) -> &mut Self {
+        self.rules.push((rule, None));
+        self
+    }
+
+    /// Adds a top-level rule with the given name to the tested grammar.
+    pub fn add_named_rule<S: Into<Cow<'a, str>>>(
+        &mut self,
+        name: S,
+        rule: &'a Rule<'a>,
+    ) -> &mut Self {
+        self.rules.push((rule, Some(name.into())));
+        self
+    }
+
+    /// Enables or disables the same optimization pass that
+    /// [`GrammarBuilder::optimize`](crate::stt::GrammarBuilder::optimize) runs, so that the test
+    /// exercises the optimized grammar.
+    pub fn optimize(&mut self, enabled: bool) -> &mut Self {
+        self.optimized = enabled;
+        self
+    }
+
+    /// Adds an utterance that the grammar must recognize, producing the given semantic
+    /// information.
+    pub fn accept<S: Into<String>>(
+        &mut self,
+        utterance: S,
+        semantics: Vec<SemanticTree>,
+    ) -> &mut Self {
+        self.cases.push(TestCase {
+            utterance: utterance.into(),
+            expected: Some(semantics),
+        });
+        self
+    }
+
+    /// Adds an utterance that the grammar must not recognize.
+    pub fn reject<S: Into<String>>(&mut self, utterance: S) -> &mut Self {
+        self.cases.push(TestCase {
+            utterance: utterance.into(),
+            expected: None,
+        });
+        self
+    }
+
+    /// Matches every utterance against the grammar and returns all the utterances that didn't
+    /// produce the expected outcome.
+    pub fn run(&self) -> Result<(), TestFailures> {
+        let mut graph = RuleGraph::from_rules(self.rules.iter().cloned());
+        if self.optimized {
+            graph = graph.optimize();
+        }
+
+        self.check(|utterance| Ok(graph.parse(utterance).map(|(_, semantics)| semantics)))
+            .unwrap()
+    }
+
+    /// Builds the grammar in a new context of the given recognizer and emulates the recognition of
+    /// every utterance, waiting up to the given timeout for each phrase. Returns an error if the
+    /// recognizer fails, otherwise returns all the utterances that didn't produce the expected
+    /// outcome.
+    ///
+    /// Since a rejected utterance doesn't produce a phrase, every rejection takes the full timeout.
+    pub fn run_emulated(
+        &self,
+        recognizer: &RecognizerBase,
+        timeout: Duration,
+    ) -> crate::Result<Result<(), TestFailures>> {
+        let ctx = SyncContext::new(recognizer)?;
+        let mut builder = ctx.grammar_builder();
+        for (rule, name) in self.rules.iter() {
+            match name {
+                Some(name) => builder.add_named_rule(name.clone(), *rule),
+                None => builder.add_rule(*rule),
+            };
+        }
+        let grammar = builder.optimize(self.optimized).build()?;
+        grammar.set_enabled(true)?;
+
+        self.check(|utterance| {
+            recognizer.emulate(utterance)?;
+            Ok(ctx.recognize(timeout)?.map(|phrase| phrase.semantics))
+        })
+    }
+
+    fn check<F>(&self, mut recognize: F) -> crate::Result<Result<(), TestFailures>>
+    where
+        F: FnMut(&str) -> crate::Result<Option<Vec<SemanticTree>>>,
+    {
+        let mut failures = Vec::new();
+        for case in self.cases.iter() {
+            let actual = recognize(&case.utterance)?;
+            if actual != case.expected {
+                failures.push(TestFailure {
+                    utterance: case.utterance.clone(),
+                    expected: case.expected.clone(),
+                    actual,
+                });
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(TestFailures(failures)))
+        }
+    }
+
+    /// Runs the test and panics with a description of every failed utterance, if there are any.
+    pub fn assert(&self) {
+        if let Err(failures) = self.run() {
+            panic!("{}", failures);
+        }
+    }
+
+    /// Runs the test against the given recognizer and panics if the recognizer fails, or with a
+    /// description of every failed utterance, if there are any.
+    pub fn assert_emulated(&self, recognizer: &RecognizerBase, timeout: Duration) {
+        if let Err(failures) = self.run_emulated(recognizer, timeout).unwrap() {
+            panic!("{}", failures);
+        }
+    }
+}
+
+/// An utterance that didn't produce the expected outcome.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestFailure {
+    /// The tested utterance.
+    pub utterance: String,
+    /// The semantic information the utterance should have produced, or `None` if the utterance
+    /// should have been rejected.
+    pub expected: Option<Vec<SemanticTree>>,
+    /// The semantic information the utterance actually produced, or `None` if the utterance was
+    /// rejected.
+    pub actual: Option<Vec<SemanticTree>>,
+}
+
+impl Display for TestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.expected, &self.actual) {
+            (Some(_), None) => write!(
+                f,
+                "{:?} should be accepted, but was rejected",
+                self.utterance
+            ),
+            (None, Some(actual)) => {
+                writeln!(
+                    f,
+                    "{:?} should be rejected, but was accepted with:",
+                    self.utterance
+                )?;
+                for line in render_trees(actual) {
+                    writeln!(f, "  {}", line)?;
+                }
+                Ok(())
+            }
+            (Some(expected), Some(actual)) => {
+                writeln!(
+                    f,
+                    "{:?} produced unexpected semantics (- expected, + actual):",
+                    self.utterance
+                )?;
+                for line in diff_lines(&render_trees(expected), &render_trees(actual)) {
+                    writeln!(f, "{}", line)?;
+                }
+                Ok(())
+            }
+            (None, None) => write!(f, "{:?} was rejected as expected", self.utterance),
+        }
+    }
+}
+
+/// The utterances that failed a [`GrammarTest`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestFailures(pub Vec<TestFailure>);
+
+impl Display for TestFailures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} utterance(s) failed the grammar test", self.0.len())?;
+        for failure in self.0.iter() {
+            writeln!(f)?;
+            writeln!(f, "{}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TestFailures {}
+
+/// Constructs a semantic tree with the given value at its root and the given sub-trees.
+pub fn tree<V: Into<SemanticValue<OsString>>>(
+    value: V,
+    children: Vec<SemanticTree>,
+) -> SemanticTree {
+    SemanticTree {
+        value: value.into(),
+        children,
+        confidence: None,
+    }
+}
+
+/// Constructs a semantic tree with the given value and no sub-trees.
+pub fn leaf<V: Into<SemanticValue<OsString>>>(value: V) -> SemanticTree {
+    tree(value, vec![])
+}
+
+fn render_trees(trees: &[SemanticTree]) -> Vec<String> {
+    fn render(trees: &[SemanticTree], depth: usize, lines: &mut Vec<String>) {
+        for tree in trees {
+            lines.push(format!(
+                "{}{}",
+                "  ".repeat(depth),
+                render_value(&tree.value)
+            ));
+            render(&tree.children, depth + 1, lines);
+        }
+    }
+
+    let mut lines = Vec::new();
+    render(trees, 0, &mut lines);
+    if lines.is_empty() {
+        lines.push("(no semantic information)".to_string());
+    }
+    lines
+}
+
+fn render_value(value: &SemanticValue<OsString>) -> String {
+    match value {
+        SemanticValue::Bool(b) => b.to_string(),
+        SemanticValue::Int(i) => i.to_string(),
+        SemanticValue::Float(f) => format!("{:?}f32", f),
+        SemanticValue::Double(d) => format!("{:?}f64", d),
+        SemanticValue::String(s) => format!("{:?}", s.to_string_lossy()),
+    }
+}
+
+// A line-based diff built on the longest common subsequence of the two inputs.
+fn diff_lines(expected: &[String], actual: &[String]) -> Vec<String> {
+    let (rows, cols) = (expected.len(), actual.len());
+    let mut lcs = vec![vec![0usize; cols + 1]; rows + 1];
+    for row in (0..rows).rev() {
+        for col in (0..cols).rev() {
+            lcs[row][col] = if expected[row] == actual[col] {
+                lcs[row + 1][col + 1] + 1
+            } else {
+                lcs[row + 1][col].max(lcs[row][col + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::with_capacity(rows + cols);
+    let (mut row, mut col) = (0, 0);
+    while row < rows || col < cols {
+        if row < rows && col < cols && expected[row] == actual[col] {
+            result.push(format!("  {}", expected[row]));
+            row += 1;
+            col += 1;
+        } else if row < rows && (col == cols || lcs[row + 1][col] >= lcs[row][col + 1]) {
+            result.push(format!("- {}", expected[row]));
+            row += 1;
+        } else {
+            result.push(format!("+ {}", actual[col]));
+            col += 1;
+        }
+    }
+    result
+}
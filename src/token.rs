@@ -1,15 +1,15 @@
-use std::ffi::OsString;
+use std::ffi::{c_void, OsString};
 
 use windows as Windows;
-use Windows::core::{IUnknown, IntoParam, Param};
-use Windows::Win32::Foundation::PWSTR;
+use Windows::core::{IUnknown, Interface, IntoParam, Param};
+use Windows::Win32::Foundation::{E_NOINTERFACE, PWSTR};
 use Windows::Win32::Media::Speech::{
     IEnumSpObjectTokens, ISpObjectToken, ISpObjectTokenCategory, SpObjectToken,
     SpObjectTokenCategory,
 };
 use Windows::Win32::System::Com::{CoCreateInstance, CLSCTX_ALL};
 
-use crate::com_util::{from_wide, next_obj, opt_str_param, ComBox, Intf};
+use crate::com_util::{from_wide, next_obj, opt_str_param, ComBox, Intf, Locale};
 use crate::Result;
 
 #[derive(Debug)]
@@ -32,6 +32,29 @@ impl Token {
         self.intf.0
     }
 
+    pub fn id(&self) -> Result<OsString> {
+        let id = unsafe { ComBox::from_raw(self.intf.GetId()?) };
+        Ok(unsafe { from_wide(&id) })
+    }
+
+    pub fn description(&self) -> Result<OsString> {
+        let value = unsafe { ComBox::from_raw(self.intf.GetStringValue(PWSTR::default())?) };
+        Ok(unsafe { from_wide(&value) })
+    }
+
+    pub fn create_instance<I: Interface>(&self) -> Result<I> {
+        let mut result: Option<I> = None;
+        unsafe {
+            self.intf.CreateInstance(
+                None,
+                CLSCTX_ALL.0,
+                &I::IID,
+                &mut result as *mut _ as *mut *mut c_void,
+            )
+        }?;
+        result.ok_or_else(|| E_NOINTERFACE.into())
+    }
+
     pub fn attr(&self, name: &str) -> Result<OsString> {
         let attrs = unsafe { self.intf.OpenKey("Attributes") }?;
         let value = unsafe { ComBox::from_raw(attrs.GetStringValue(name)?) };
@@ -94,16 +117,64 @@ impl Category {
         req_attrs: Option<S>,
         opt_attrs: Option<S>,
     ) -> Result<Tokens> {
-        unsafe {
+        let intf = unsafe {
             self.intf.EnumTokens(
                 opt_str_param(req_attrs).abi(),
                 opt_str_param(opt_attrs).abi(),
             )
-        }
-        .map(|intf| Tokens { intf: Intf(intf) })
+        }?;
+        Ok(Tokens { intf: Intf(intf) })
+    }
+
+    pub fn default_token_id(&self) -> Result<OsString> {
+        let id = unsafe { ComBox::from_raw(self.intf.GetDefaultTokenId()?) };
+        Ok(unsafe { from_wide(&id) })
     }
 
     pub fn default_token(&self) -> Result<Token> {
-        unsafe { self.intf.GetDefaultTokenId() }.and_then(Token::new)
+        let id = unsafe { self.intf.GetDefaultTokenId() }?;
+        Token::new(id)
     }
 }
+
+pub(crate) struct TokenSelector {
+    sapi_expr: String,
+}
+
+impl TokenSelector {
+    pub fn new() -> Self {
+        Self {
+            sapi_expr: String::new(),
+        }
+    }
+
+    pub fn append_condition(mut self, prefix: &str, val: &str) -> Self {
+        if !self.sapi_expr.is_empty() {
+            self.sapi_expr.push(';')
+        }
+        self.sapi_expr.push_str(prefix);
+        self.sapi_expr.push_str(val);
+        self
+    }
+
+    pub fn append_language_condition(self, prefix: &str, language: &str) -> Self {
+        if let Ok(locale) = language.parse::<Locale>() {
+            self.append_condition(prefix, &format!("{:X}", locale.lcid()))
+        } else {
+            self
+        }
+    }
+
+    pub fn into_sapi_expr(self) -> String {
+        self.sapi_expr
+    }
+}
+
+// Recognition engines can list several languages, separated by semicolons, with the primary one
+// first
+pub(crate) fn token_language(token: &Token) -> Option<OsString> {
+    let lcids = token.attr("language").ok()?;
+    let lcid = lcids.to_str()?.split(';').next()?;
+    let lcid = u32::from_str_radix(lcid, 16).ok()?;
+    Some(Locale::new(lcid).name())
+}
@@ -5,14 +5,13 @@
 mod rt;
 #[cfg(feature = "tokio-stt")]
 mod stt;
-#[cfg(feature = "tokio-tts")]
-mod tts;
 
+#[cfg(feature = "tokio-tts")]
+pub use crate::tts::asynchronous::AsyncSynthesizer;
 #[cfg(feature = "tokio-rt")]
 pub use rt::BuilderExt;
 #[cfg(feature = "tokio-stt")]
 pub use stt::{
-    BroadcastContext, BroadcastResult, BroadcastSubscriber, UnicastContext, UnicastSubscriber,
+    AudioLevelSubscriber, BroadcastContext, BroadcastResult, BroadcastSubscriber, HypothesisResult,
+    HypothesisSubscriber, UnicastContext, UnicastSubscriber,
 };
-#[cfg(feature = "tokio-tts")]
-pub use tts::AsyncSynthesizer;
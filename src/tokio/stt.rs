@@ -1,21 +1,56 @@
 use std::ops::Deref;
+use std::sync::{Arc, Mutex};
 
 use tokio::sync::broadcast::error::RecvError;
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, mpsc, watch};
 
-use crate::stt::{Context, EventfulContext, Phrase, Recognizer};
+use crate::stt::{
+    AudioLevel, Context, EventHandler, EventfulContext, Intents, Interference, Phrase, Recognition,
+    RecognizedIntent, RecognizerBase,
+};
 use crate::Result;
 
 /// A subscriber that can be awaited for recognized phrases.
+///
+/// Once its context is closed or dropped, the subscriber yields the phrases that are still
+/// buffered, followed by `None`.
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio-stt")))]
 pub struct UnicastSubscriber {
-    rx: mpsc::Receiver<Phrase>,
+    rx: mpsc::Receiver<Recognition>,
 }
 
 impl UnicastSubscriber {
-    /// Completes when the engine recognizes a phrase.
-    pub async fn recognize(&mut self) -> Phrase {
-        self.rx.recv().await.unwrap()
+    /// Completes when the engine recognizes a phrase, or with `None` if the context has been
+    /// closed. Phrases rejected for having less than the minimum confidence are skipped.
+    pub async fn recognize(&mut self) -> Option<Phrase> {
+        loop {
+            if let Recognition::Accepted(phrase) = self.recognize_outcome().await? {
+                return Some(phrase);
+            }
+        }
+    }
+
+    /// Completes when the engine recognizes a phrase that can be decoded into one of the given
+    /// intents, or with `None` if the context has been closed. Phrases that cannot be decoded are
+    /// skipped, as are phrases rejected for having less than the minimum confidence.
+    pub async fn recognize_intent<T>(
+        &mut self,
+        intents: &Intents<T>,
+    ) -> Option<RecognizedIntent<T>> {
+        loop {
+            if let Some(intent) = intents.decode(self.recognize().await?) {
+                return Some(intent);
+            }
+        }
+    }
+
+    /// Completes when the engine concludes an attempt to recognize a phrase, or with `None` if the
+    /// context has been closed. Unlike [`recognize`](Self::recognize), this also yields rejected
+    /// phrases, false recognitions, and interference with the audio. False recognitions and
+    /// interference must be enabled with
+    /// [`Context::set_interest`](crate::stt::Context::set_interest).
+    pub async fn recognize_outcome(&mut self) -> Option<Recognition> {
+        self.rx.recv().await
     }
 }
 
@@ -25,49 +60,147 @@ impl UnicastSubscriber {
 pub enum BroadcastResult {
     /// Holds a phrase that has been recognized
     Phrase(Phrase),
+    /// Holds a phrase that has been recognized with less than the minimum confidence
+    Rejected(Phrase),
+    /// Indicates that the engine heard speech that didn't match any of the enabled rules, and
+    /// holds the partial phrase the engine was able to recognize, if any
+    FalseRecognition(Option<Phrase>),
+    /// Indicates that the engine detected a problem with the audio
+    Interference(Interference),
     /// Indicates how many phrases have been skipped because the subscriber lagged too far behind
     Lagged(u64),
+    /// Indicates that the context has been closed, and that no more phrases will be received
+    Closed,
 }
 
 /// A subscriber that can be awaited for recognized phrases.
+///
+/// Once its context is closed or dropped, the subscriber yields the phrases that are still
+/// buffered, followed by [`BroadcastResult::Closed`].
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio-stt")))]
 pub struct BroadcastSubscriber {
-    rx: broadcast::Receiver<Phrase>,
+    rx: broadcast::Receiver<Recognition>,
 }
 
 impl BroadcastSubscriber {
-    /// Completes when the engine recognizes a phrase.
+    /// Completes when the engine concludes an attempt to recognize a phrase, when the subscriber
+    /// lags too far behind, or when the context is closed.
     pub async fn recognize(&mut self) -> BroadcastResult {
         match self.rx.recv().await {
-            Ok(phrase) => BroadcastResult::Phrase(phrase),
+            Ok(Recognition::Accepted(phrase)) => BroadcastResult::Phrase(phrase),
+            Ok(Recognition::Rejected(phrase)) => BroadcastResult::Rejected(phrase),
+            Ok(Recognition::FalseRecognition(phrase)) => BroadcastResult::FalseRecognition(phrase),
+            Ok(Recognition::Interference(interference)) => {
+                BroadcastResult::Interference(interference)
+            }
             Err(RecvError::Lagged(skipped)) => BroadcastResult::Lagged(skipped),
-            Err(err) => panic!("{}", err),
+            Err(RecvError::Closed) => BroadcastResult::Closed,
+        }
+    }
+}
+
+/// The result of awaiting a [`HypothesisSubscriber`].
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-stt")))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum HypothesisResult {
+    /// Holds a hypothesis the engine has formed
+    Hypothesis(Phrase),
+    /// Indicates how many hypotheses have been skipped because the subscriber lagged too far
+    /// behind
+    Lagged(u64),
+    /// Indicates that the context has been closed, and that no more hypotheses will be received
+    Closed,
+}
+
+/// A subscriber that can be awaited for hypotheses, i.e. partial phrases the engine reports while
+/// the user is speaking. Hypotheses must be enabled with
+/// [`Context::set_interest`](crate::stt::Context::set_interest).
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-stt")))]
+pub struct HypothesisSubscriber {
+    rx: broadcast::Receiver<Phrase>,
+}
+
+impl HypothesisSubscriber {
+    /// Completes when the engine forms a hypothesis, or yields a [`HypothesisResult::Lagged`] if
+    /// the subscriber lagged too far behind, or a [`HypothesisResult::Closed`] if the context has
+    /// been closed.
+    pub async fn hypothesis(&mut self) -> HypothesisResult {
+        match self.rx.recv().await {
+            Ok(phrase) => HypothesisResult::Hypothesis(phrase),
+            Err(RecvError::Lagged(skipped)) => HypothesisResult::Lagged(skipped),
+            Err(RecvError::Closed) => HypothesisResult::Closed,
         }
     }
 }
 
+/// A subscriber that can be awaited for changes in the level of the audio the engine is hearing.
+/// Audio levels must be enabled with [`Context::set_interest`](crate::stt::Context::set_interest).
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-stt")))]
+pub struct AudioLevelSubscriber {
+    rx: watch::Receiver<AudioLevel>,
+}
+
+impl AudioLevelSubscriber {
+    /// Returns the most recently reported audio level.
+    pub fn current(&self) -> AudioLevel {
+        *self.rx.borrow()
+    }
+
+    /// Completes when the engine reports a new audio level, or with `None` if the context has been
+    /// closed. If more than one level was reported since the last call, only the most recent one is
+    /// returned.
+    pub async fn changed(&mut self) -> Option<AudioLevel> {
+        self.rx.changed().await.ok()?;
+        Some(*self.rx.borrow())
+    }
+}
+
 /// A recognition context paired with a single subscriber that can be awaited for recognition.
+///
+/// Closing or dropping the context ends all its subscribers.
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio-stt")))]
 pub struct UnicastContext {
     base: EventfulContext,
+    senders: SharedSenders<mpsc::Sender<Recognition>>,
+    audio_level_rx: watch::Receiver<AudioLevel>,
 }
 
 impl UnicastContext {
     /// Creates a new recognition context for the given recognizer, configured to buffer up to the
     /// given number of recognized phrases. If a new phrase is recognized while the buffer is full,
     /// it will be silently dropped.
-    pub fn new(recognizer: &Recognizer, buffer: usize) -> Result<(Self, UnicastSubscriber)> {
-        let (tx, rx) = mpsc::channel::<Phrase>(buffer);
-        let handler = move |phrase| {
-            let _ = tx.try_send(phrase);
-        };
+    pub fn new(recognizer: &RecognizerBase, buffer: usize) -> Result<(Self, UnicastSubscriber)> {
+        let (tx, rx) = mpsc::channel::<Recognition>(buffer);
+        let (senders, audio_level_rx) = Senders::new(tx, buffer);
         Ok((
             Self {
-                base: EventfulContext::new(recognizer, handler)?,
+                base: EventfulContext::new(recognizer, ChannelHandler(senders.clone()))?,
+                senders,
+                audio_level_rx,
             },
             UnicastSubscriber { rx },
         ))
     }
+
+    /// Creates a subscriber for the hypotheses formed in this context.
+    pub fn hypotheses(&self) -> HypothesisSubscriber {
+        HypothesisSubscriber {
+            rx: subscribe(&self.senders, |senders| &senders.hypothesis_tx),
+        }
+    }
+
+    /// Creates a subscriber for the audio levels reported to this context.
+    pub fn audio_levels(&self) -> AudioLevelSubscriber {
+        AudioLevelSubscriber {
+            rx: self.audio_level_rx.clone(),
+        }
+    }
+
+    /// Closes the context, so that its subscribers stop waiting. The phrases recognized afterwards
+    /// are dropped.
+    pub fn close(&self) {
+        self.senders.lock().unwrap().take();
+    }
 }
 
 impl Deref for UnicastContext {
@@ -77,11 +210,20 @@ impl Deref for UnicastContext {
     }
 }
 
+impl Drop for UnicastContext {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
 /// A recognition context paired with a one or more subscribers that can be awaited for recognition.
+///
+/// Closing or dropping the context ends all its subscribers.
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio-stt")))]
 pub struct BroadcastContext {
     base: EventfulContext,
-    tx: broadcast::Sender<Phrase>,
+    senders: SharedSenders<broadcast::Sender<Recognition>>,
+    audio_level_rx: watch::Receiver<AudioLevel>,
 }
 
 impl BroadcastContext {
@@ -89,29 +231,46 @@ impl BroadcastContext {
     /// given number of recognized phrases. If a new phrase is recognized while one or more
     /// subscribers haven't received it, it will be dropped and those subscribers will yield a
     /// [`BroadcastResult::Lagged`] on next await.
-    pub fn new(recognizer: &Recognizer, buffer: usize) -> Result<(Self, BroadcastSubscriber)> {
-        let (tx, rx) = broadcast::channel::<Phrase>(buffer);
-        let handler = {
-            let tx = tx.clone();
-            move |phrase| {
-                let _ = tx.send(phrase);
-            }
-        };
+    pub fn new(recognizer: &RecognizerBase, buffer: usize) -> Result<(Self, BroadcastSubscriber)> {
+        let (tx, rx) = broadcast::channel::<Recognition>(buffer);
+        let (senders, audio_level_rx) = Senders::new(tx, buffer);
         Ok((
             Self {
-                base: EventfulContext::new(recognizer, handler)?,
-                tx,
+                base: EventfulContext::new(recognizer, ChannelHandler(senders.clone()))?,
+                senders,
+                audio_level_rx,
             },
             BroadcastSubscriber { rx },
         ))
     }
 
-    /// Creates a subscriber for this context.
+    /// Creates a subscriber for this context. If the context has been closed, the subscriber
+    /// yields [`BroadcastResult::Closed`] right away.
     pub fn subscribe(&self) -> BroadcastSubscriber {
         BroadcastSubscriber {
-            rx: self.tx.subscribe(),
+            rx: subscribe(&self.senders, |senders| &senders.recognition_tx),
+        }
+    }
+
+    /// Creates a subscriber for the hypotheses formed in this context.
+    pub fn hypotheses(&self) -> HypothesisSubscriber {
+        HypothesisSubscriber {
+            rx: subscribe(&self.senders, |senders| &senders.hypothesis_tx),
+        }
+    }
+
+    /// Creates a subscriber for the audio levels reported to this context.
+    pub fn audio_levels(&self) -> AudioLevelSubscriber {
+        AudioLevelSubscriber {
+            rx: self.audio_level_rx.clone(),
         }
     }
+
+    /// Closes the context, so that its subscribers stop waiting. The phrases recognized afterwards
+    /// are dropped.
+    pub fn close(&self) {
+        self.senders.lock().unwrap().take();
+    }
 }
 
 impl Deref for BroadcastContext {
@@ -120,3 +279,105 @@ impl Deref for BroadcastContext {
         &self.base
     }
 }
+
+impl Drop for BroadcastContext {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+// The event handler is owned by SAPI, which may keep it alive after the context is dropped, so the
+// context shares the senders with the handler, and drops them when it's closed
+type SharedSenders<T> = Arc<Mutex<Option<Senders<T>>>>;
+
+struct Senders<T> {
+    recognition_tx: T,
+    hypothesis_tx: broadcast::Sender<Phrase>,
+    audio_level_tx: watch::Sender<AudioLevel>,
+}
+
+impl<T> Senders<T> {
+    fn new(recognition_tx: T, buffer: usize) -> (SharedSenders<T>, watch::Receiver<AudioLevel>) {
+        let (hypothesis_tx, _) = broadcast::channel::<Phrase>(buffer);
+        let (audio_level_tx, audio_level_rx) = watch::channel(AudioLevel::default());
+        let senders = Self {
+            recognition_tx,
+            hypothesis_tx,
+            audio_level_tx,
+        };
+        (Arc::new(Mutex::new(Some(senders))), audio_level_rx)
+    }
+}
+
+fn subscribe<T, U: Clone, F: FnOnce(&Senders<T>) -> &broadcast::Sender<U>>(
+    senders: &Mutex<Option<Senders<T>>>,
+    select: F,
+) -> broadcast::Receiver<U> {
+    match senders.lock().unwrap().as_ref() {
+        Some(senders) => select(senders).subscribe(),
+        // Without a sender, the receiver is closed from the start
+        None => broadcast::channel(1).1,
+    }
+}
+
+trait RecognitionSender: Send {
+    fn send_recognition(&self, recognition: Recognition);
+}
+
+impl RecognitionSender for mpsc::Sender<Recognition> {
+    fn send_recognition(&self, recognition: Recognition) {
+        let _ = self.try_send(recognition);
+    }
+}
+
+impl RecognitionSender for broadcast::Sender<Recognition> {
+    fn send_recognition(&self, recognition: Recognition) {
+        let _ = self.send(recognition);
+    }
+}
+
+struct ChannelHandler<T>(SharedSenders<T>);
+
+impl<T> ChannelHandler<T> {
+    fn with_senders<F: FnOnce(&Senders<T>)>(&self, f: F) {
+        if let Some(senders) = self.0.lock().unwrap().as_ref() {
+            f(senders)
+        }
+    }
+}
+
+impl<T: RecognitionSender> ChannelHandler<T> {
+    fn send_recognition(&self, recognition: Recognition) {
+        self.with_senders(|senders| senders.recognition_tx.send_recognition(recognition))
+    }
+}
+
+impl<T: RecognitionSender> EventHandler for ChannelHandler<T> {
+    fn on_recognition(&self, phrase: Phrase) {
+        self.send_recognition(Recognition::Accepted(phrase))
+    }
+
+    fn on_rejection(&self, phrase: Phrase) {
+        self.send_recognition(Recognition::Rejected(phrase))
+    }
+
+    fn on_false_recognition(&self, phrase: Option<Phrase>) {
+        self.send_recognition(Recognition::FalseRecognition(phrase))
+    }
+
+    fn on_interference(&self, interference: Interference) {
+        self.send_recognition(Recognition::Interference(interference))
+    }
+
+    fn on_hypothesis(&self, phrase: Phrase) {
+        self.with_senders(|senders| {
+            let _ = senders.hypothesis_tx.send(phrase);
+        })
+    }
+
+    fn on_audio_level(&self, level: AudioLevel) {
+        self.with_senders(|senders| {
+            let _ = senders.audio_level_tx.send(level);
+        })
+    }
+}
@@ -2,6 +2,10 @@ use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
 
+// Both channels work with any executor, so use whichever one is enabled
+#[cfg(feature = "futures-tts")]
+use futures::channel::oneshot::{channel, Receiver, Sender};
+#[cfg(not(feature = "futures-tts"))]
 use tokio::sync::oneshot::{channel, Receiver, Sender};
 
 use crate::tts::{EventfulSynthesizer, Speech, Synthesizer};
@@ -12,7 +16,7 @@ enum PendingSpeech {
     Finished,
 }
 
-#[cfg_attr(docsrs, doc(cfg(feature = "tokio-tts")))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "tokio-tts", feature = "futures-tts"))))]
 /// A speech synthesizer that returns a future for every speech it renders.
 pub struct AsyncSynthesizer {
     base: EventfulSynthesizer,
@@ -9,7 +9,8 @@
 //! * [`EventfulSynthesizer`] will return immediately and call the supplied event handler when the
 //! speech is finished.
 //!
-//! For asynchronous synthesis, see the [`tokio`](crate::tokio) module.
+//! For asynchronous synthesis, see the [`tokio`](crate::tokio) module, or the
+//! [`futures`](crate::futures) module for other runtimes.
 //!
 //! All synthesizers share the methods defined in the [`Synthesizer`] struct.
 //!
@@ -18,6 +19,8 @@
 //! The user can install a variety of voices on their machine. The [`installed_voices`] function
 //! allows iterating through all the installed voices, filtered by the provided criteria.
 
+#[cfg(any(feature = "tokio-tts", feature = "futures-tts"))]
+pub(crate) mod asynchronous;
 mod speech;
 mod synthesizer;
 mod voice;
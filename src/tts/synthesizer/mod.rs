@@ -1,9 +1,10 @@
 use windows as Windows;
 use Windows::core::IUnknown;
+use Windows::Win32::Foundation::E_INVALIDARG;
 use Windows::Win32::Media::Speech::{ISpVoice, SpVoice};
 use Windows::Win32::System::Com::{CoCreateInstance, CLSCTX_ALL};
 
-use crate::audio::AudioStream;
+use crate::audio::{AudioDevice, AudioDirection, AudioStream};
 use crate::com_util::{out_to_ret, Intf};
 use crate::token::Token;
 use crate::Result;
@@ -20,16 +21,22 @@ pub use sync::SyncSynthesizer;
 pub enum SpeechOutput {
     /// Output to the default audio device on the system
     Default,
+    /// Output to the given audio device, which must be an output device
+    Device(AudioDevice),
     /// Write to the given stream
     Stream(AudioStream),
 }
 
 impl SpeechOutput {
-    fn to_sapi(self) -> Option<IUnknown> {
-        match self {
+    fn to_sapi(self) -> Result<Option<IUnknown>> {
+        Ok(match self {
             Self::Default => None,
+            Self::Device(device) if device.direction() != AudioDirection::Output => {
+                return Err(E_INVALIDARG.into())
+            }
+            Self::Device(device) => Some(device.token.to_sapi().0),
             Self::Stream(stream) => Some(stream.to_sapi().0),
-        }
+        })
     }
 }
 
@@ -40,13 +47,14 @@ pub struct Synthesizer {
 
 impl Synthesizer {
     fn new() -> Result<Self> {
-        unsafe { CoCreateInstance(&SpVoice, None, CLSCTX_ALL) }
-            .map(|intf| Self { intf: Intf(intf) })
+        let intf = unsafe { CoCreateInstance(&SpVoice, None, CLSCTX_ALL) }?;
+        Ok(Self { intf: Intf(intf) })
     }
 
     /// Configures the synthesizer to render its speech to the given output destination.
     pub fn set_output(&self, output: SpeechOutput, allow_fmt_changes: bool) -> Result<()> {
-        unsafe { self.intf.SetOutput(output.to_sapi(), allow_fmt_changes) }
+        unsafe { self.intf.SetOutput(output.to_sapi()?, allow_fmt_changes) }?;
+        Ok(())
     }
 
     /// Returns the default rate of speech for this synthesizer.
@@ -56,7 +64,8 @@ impl Synthesizer {
 
     /// Returns the default voice this synthesizer will use to render speech.
     pub fn voice(&self) -> Result<Voice> {
-        unsafe { self.intf.GetVoice() }.map(|intf| Voice {
+        let intf = unsafe { self.intf.GetVoice() }?;
+        Ok(Voice {
             token: Token::from_sapi(intf),
         })
     }
@@ -68,24 +77,28 @@ impl Synthesizer {
 
     /// Sets the default rate of speech for this synthesizer.
     pub fn set_rate<R: Into<Rate>>(&self, rate: R) -> Result<()> {
-        unsafe { self.intf.SetRate(rate.into().value()) }
+        unsafe { self.intf.SetRate(rate.into().value()) }?;
+        Ok(())
     }
 
     /// Sets the default voice this synthesizer will use to render speech.
     pub fn set_voice(&self, voice: &Voice) -> Result<()> {
-        unsafe { self.intf.SetVoice(&voice.token) }
+        unsafe { self.intf.SetVoice(&voice.token) }?;
+        Ok(())
     }
 
     /// Sets the default speech volume for this synthesizer.
     pub fn set_volume<V: Into<Volume>>(&self, volume: V) -> Result<()> {
-        unsafe { self.intf.SetVolume(volume.into().sapi_value()) }
+        unsafe { self.intf.SetVolume(volume.into().sapi_value()) }?;
+        Ok(())
     }
 
     fn speak<'s, S: Into<Speech<'s>>>(&self, speech: S, base_flags: u32) -> Result<u32> {
         let speech = speech.into();
-        unsafe {
+        let id = unsafe {
             self.intf
                 .Speak(speech.contents(), speech.flags() | base_flags)
-        }
+        }?;
+        Ok(id)
     }
 }
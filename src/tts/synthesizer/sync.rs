@@ -36,7 +36,8 @@ impl SyncSynthesizer {
                     .map(|dur| dur.as_millis() as u32)
                     .unwrap_or(INFINITE),
             )
-        }
+        }?;
+        Ok(())
     }
 }
 
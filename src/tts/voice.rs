@@ -4,7 +4,7 @@ use std::str::FromStr;
 use strum_macros::{EnumString, IntoStaticStr};
 
 use crate::com_util::Locale;
-use crate::token::{Category, Token};
+use crate::token::{Category, Token, TokenSelector};
 use crate::Result;
 
 /// Specifies the age of a voice.
@@ -69,14 +69,14 @@ impl Voice {
 
 /// Encapsulates the criteria for selecting a voice.
 pub struct VoiceSelector {
-    sapi_expr: String,
+    selector: TokenSelector,
 }
 
 impl VoiceSelector {
     /// Creates a new, empty selector.
     pub fn new() -> Self {
         Self {
-            sapi_expr: String::new(),
+            selector: TokenSelector::new(),
         }
     }
 
@@ -119,34 +119,31 @@ impl VoiceSelector {
     /// Returns a selector that requires the voice to have the given language, along with all the
     /// previously specified conditions.
     pub fn language_eq<S: AsRef<str>>(self, language: S) -> Self {
-        if let Ok(locale) = language.as_ref().parse::<Locale>() {
-            self.append_condition("language=", &format!("{:X}", locale.lcid()))
-        } else {
-            self
+        Self {
+            selector: self
+                .selector
+                .append_language_condition("language=", language.as_ref()),
         }
     }
 
     /// Returns a selector that requires the voice to have a language different from the one given
     /// here, along with all the previously specified conditions.
     pub fn language_ne<S: AsRef<str>>(self, language: S) -> Self {
-        if let Ok(locale) = language.as_ref().parse::<Locale>() {
-            self.append_condition("language!=", &format!("{:X}", locale.lcid()))
-        } else {
-            self
+        Self {
+            selector: self
+                .selector
+                .append_language_condition("language!=", language.as_ref()),
         }
     }
 
-    fn append_condition(mut self, prefix: &str, val: &str) -> Self {
-        if !self.sapi_expr.is_empty() {
-            self.sapi_expr.push(';')
+    fn append_condition(self, prefix: &str, val: &str) -> Self {
+        Self {
+            selector: self.selector.append_condition(prefix, val),
         }
-        self.sapi_expr.push_str(prefix);
-        self.sapi_expr.push_str(val);
-        self
     }
 
     pub(crate) fn into_sapi_expr(self) -> String {
-        self.sapi_expr
+        self.selector.into_sapi_expr()
     }
 }
 
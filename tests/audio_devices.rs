@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use sapi_lite::audio::{input_devices, output_devices, AudioDevice, AudioDirection};
+use sapi_lite::tts::{SpeechOutput, SyncSynthesizer};
+
+#[test]
+fn test_input_devices() {
+    sapi_lite::initialize().unwrap();
+
+    let devices: Vec<_> = input_devices().unwrap().collect();
+    assert!(devices.iter().filter(|device| device.is_default()).count() <= 1);
+    for device in devices {
+        assert_eq!(AudioDirection::Input, device.direction());
+        let id = device.id().unwrap();
+        let resolved = AudioDevice::from_id(id.to_str().unwrap()).unwrap();
+        assert_eq!(AudioDirection::Input, resolved.direction());
+        assert_eq!(device.is_default(), resolved.is_default());
+
+        // An input device can't be used for speech output
+        let synth = SyncSynthesizer::new().unwrap();
+        assert!(synth
+            .set_output(SpeechOutput::Device(resolved), false)
+            .is_err());
+    }
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_output_devices() {
+    sapi_lite::initialize().unwrap();
+
+    let devices: Vec<_> = output_devices().unwrap().collect();
+    assert!(devices.iter().filter(|device| device.is_default()).count() <= 1);
+    if let Some(device) = devices.into_iter().next() {
+        let id = device.id().unwrap();
+        let resolved = AudioDevice::from_id(id.to_str().unwrap()).unwrap();
+        assert_eq!(Some(id), resolved.id());
+        assert_eq!(AudioDirection::Output, resolved.direction());
+        assert_eq!(device.is_default(), resolved.is_default());
+
+        let synth = SyncSynthesizer::new().unwrap();
+        synth
+            .set_output(SpeechOutput::Device(resolved), false)
+            .unwrap();
+        synth.speak("", Some(Duration::from_secs(1))).unwrap();
+    }
+
+    sapi_lite::finalize();
+}
@@ -0,0 +1,523 @@
+use std::ffi::OsString;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use sapi_lite::stt::{
+    installed_engines, ChannelContext, ConfidenceThreshold, Context, ContextEvent, EventInterest,
+    EventfulContext, Grammar, Intent, Intents, OverflowPolicy, Phrase, Recognition, Recognizer,
+    Router, Rule, RuleArena, SharedRecognizer, SyncContext,
+};
+use sapi_lite::testing::{leaf, tree};
+use sapi_lite::Error;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+#[test]
+fn test_emulated_sync() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let ctx = SyncContext::new(&recog).unwrap();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("have a very very good evening").unwrap();
+    let phrase = ctx.recognize(TIMEOUT).unwrap().unwrap();
+    assert_eq!("have a very very good evening", phrase.text);
+    assert_eq!(
+        vec![tree("how_good", vec![leaf(1), leaf(1)]), leaf("pm")],
+        phrase.semantics
+    );
+
+    recog.emulate("have a good night").unwrap();
+    assert_eq!(None, ctx.recognize(TIMEOUT).unwrap());
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_emulated_iteration() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let ctx = SyncContext::new(&recog).unwrap();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("have a good morning").unwrap();
+    recog.emulate("have a good night").unwrap();
+    recog.emulate("have a very good evening").unwrap();
+    let texts: Vec<OsString> = ctx
+        .recognitions(TIMEOUT)
+        .map(|phrase| phrase.unwrap().text)
+        .collect();
+    assert_eq!(
+        vec![
+            OsString::from("have a good morning"),
+            OsString::from("have a very good evening")
+        ],
+        texts
+    );
+
+    let interrupter = ctx.interrupter();
+    let start = Instant::now();
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(TIMEOUT);
+        interrupter.interrupt();
+    });
+    assert!(ctx.events(Duration::from_secs(60)).next().is_none());
+    assert!(start.elapsed() < Duration::from_secs(60));
+    thread.join().unwrap();
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_emulated_recognize_keeps_waiting() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let ctx = SyncContext::new(&recog).unwrap();
+    ctx.set_interest(EventInterest {
+        hypotheses: true,
+        ..Default::default()
+    })
+    .unwrap();
+    let grammar = ctx
+        .grammar_builder()
+        .add_named_rule("delete", &Rule::text("delete everything"))
+        .add_named_rule("save", &Rule::text("save everything"))
+        .build()
+        .unwrap();
+    grammar.set_enabled(true).unwrap();
+    ctx.set_rule_min_confidence("delete", Some(ConfidenceThreshold::Score(f32::MAX)));
+
+    // The hypotheses and the rejected phrase wake up the context, but don't end the wait
+    recog.emulate("delete everything").unwrap();
+    recog.emulate("save everything").unwrap();
+    let phrase = ctx.recognize(TIMEOUT).unwrap().unwrap();
+    assert_eq!("save everything", phrase.text);
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_grammar_error() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let ctx = SyncContext::new(&recog).unwrap();
+    let result = ctx
+        .grammar_builder()
+        .add_named_rule("save", &Rule::text("save everything"))
+        .add_named_rule("empty", &Rule::choice(vec![]))
+        .build();
+    match result {
+        Err(Error::Grammar { rule, .. }) => assert_eq!(Some("empty".to_string()), rule),
+        _ => panic!("expected a grammar error"),
+    }
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_emulated_confidence() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let ctx = SyncContext::new(&recog).unwrap();
+    let grammar = ctx
+        .grammar_builder()
+        .add_named_rule("delete", &Rule::text("delete everything"))
+        .add_named_rule("save", &Rule::text("save everything"))
+        .build()
+        .unwrap();
+    grammar.set_enabled(true).unwrap();
+
+    ctx.set_rule_min_confidence("delete", Some(ConfidenceThreshold::Score(f32::MAX)));
+
+    recog.emulate("save everything").unwrap();
+    let phrase = ctx.recognize(TIMEOUT).unwrap().unwrap();
+    assert_eq!(Some("save".into()), phrase.rule.name);
+    assert_eq!(grammar.id(), phrase.grammar_id);
+    assert_eq!(2, phrase.elements.len());
+
+    recog.emulate("delete everything").unwrap();
+    match ctx.recognize_outcome(TIMEOUT).unwrap() {
+        Some(Recognition::Rejected(phrase)) => assert_eq!("delete everything", phrase.text),
+        outcome => panic!("unexpected outcome: {:?}", outcome),
+    }
+
+    ctx.set_rule_min_confidence("delete", None);
+    recog.emulate("delete everything").unwrap();
+    assert!(matches!(
+        ctx.recognize_outcome(TIMEOUT).unwrap(),
+        Some(Recognition::Accepted(_))
+    ));
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_emulated_false_recognition() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let ctx = SyncContext::new(&recog).unwrap();
+    ctx.set_interest(EventInterest {
+        false_recognitions: true,
+        ..Default::default()
+    })
+    .unwrap();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("have a good night").unwrap();
+    assert!(matches!(
+        ctx.recognize_outcome(TIMEOUT).unwrap(),
+        Some(Recognition::FalseRecognition(_))
+    ));
+
+    recog.emulate("have a good morning").unwrap();
+    let phrase = ctx.recognize(TIMEOUT).unwrap().unwrap();
+    assert_eq!("have a good morning", phrase.text);
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_emulated_alternates() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let ctx = SyncContext::new(&recog).unwrap();
+    ctx.set_max_alternates(3).unwrap();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("have a good evening").unwrap();
+    let phrase = ctx.recognize(TIMEOUT).unwrap().unwrap();
+    assert!(!phrase.alternates.is_empty());
+    assert!(phrase.alternates.len() <= 3);
+    assert_eq!("have a good evening", phrase.alternates[0].text);
+    assert!(phrase
+        .alternates
+        .iter()
+        .all(|alt| alt.alternates.is_empty()));
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_emulated_with_hypotheses() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let ctx = SyncContext::new(&recog).unwrap();
+    ctx.set_interest(EventInterest {
+        hypotheses: true,
+        ..Default::default()
+    })
+    .unwrap();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("have a good evening").unwrap();
+    let mut hypotheses = Vec::new();
+    let phrase = ctx
+        .recognize_with_hypotheses(TIMEOUT, |hypothesis| hypotheses.push(hypothesis))
+        .unwrap()
+        .unwrap();
+    assert_eq!("have a good evening", phrase.text);
+    for hypothesis in hypotheses {
+        assert!(hypothesis.alternates.is_empty());
+    }
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_emulated_eventful() {
+    sapi_lite::initialize().unwrap();
+
+    let (tx, rx) = mpsc::sync_channel(1);
+    let recog = Recognizer::new().unwrap();
+    let ctx = EventfulContext::new(&recog, move |phrase: Phrase| {
+        tx.send(phrase).unwrap();
+    })
+    .unwrap();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("have a good morning").unwrap();
+    let phrase = rx.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!("have a good morning", phrase.text);
+    assert_eq!(vec![tree("how_good", vec![]), leaf("am")], phrase.semantics);
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_emulated_channel() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    assert!(ChannelContext::new(&recog, 0, OverflowPolicy::Block).is_err());
+
+    let (ctx, rx) = ChannelContext::new(&recog, 4, OverflowPolicy::Block).unwrap();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("have a good morning").unwrap();
+    let phrase = rx.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!("have a good morning", phrase.text);
+    assert_eq!(vec![tree("how_good", vec![]), leaf("am")], phrase.semantics);
+    drop(grammar);
+    drop(ctx);
+
+    let (ctx, rx) = ChannelContext::with_events(&recog, 4, OverflowPolicy::DropNewest).unwrap();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("have a good evening").unwrap();
+    match rx.recv_timeout(TIMEOUT).unwrap() {
+        ContextEvent::Recognition(Recognition::Accepted(phrase)) => {
+            assert_eq!("have a good evening", phrase.text)
+        }
+        event => panic!("unexpected event: {:?}", event),
+    }
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_emulated_router() {
+    sapi_lite::initialize().unwrap();
+
+    let (tx, rx) = mpsc::sync_channel(1);
+    let mut router = Router::new();
+    let save_tx = tx.clone();
+    router.route("save", move |phrase: Phrase| {
+        save_tx.send(("save", phrase.text)).unwrap();
+    });
+    router.fallback(move |outcome: Recognition| {
+        let text = match outcome {
+            Recognition::Accepted(phrase) => phrase.text,
+            Recognition::Rejected(phrase) => phrase.text,
+            _ => OsString::new(),
+        };
+        tx.send(("fallback", text)).unwrap();
+    });
+
+    let recog = Recognizer::new().unwrap();
+    let ctx = EventfulContext::new(&recog, router).unwrap();
+    let grammar = ctx
+        .grammar_builder()
+        .add_named_rule("delete", &Rule::text("delete everything"))
+        .add_named_rule("save", &Rule::text("save everything"))
+        .build()
+        .unwrap();
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("save everything").unwrap();
+    assert_eq!(
+        ("save", "save everything".into()),
+        rx.recv_timeout(TIMEOUT).unwrap()
+    );
+
+    recog.emulate("delete everything").unwrap();
+    assert_eq!(
+        ("fallback", "delete everything".into()),
+        rx.recv_timeout(TIMEOUT).unwrap()
+    );
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_engine_selection() {
+    sapi_lite::initialize().unwrap();
+
+    let engine = installed_engines(None, None).unwrap().next().unwrap();
+    let recog = Recognizer::with_engine(&engine).unwrap();
+    assert_eq!(engine.name(), recog.engine().unwrap().name());
+
+    recog.set_engine(&engine).unwrap();
+    let ctx = SyncContext::new(&recog).unwrap();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("have a good evening").unwrap();
+    assert!(ctx.recognize(TIMEOUT).unwrap().is_some());
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_shared_recognizer() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = SharedRecognizer::new().unwrap();
+    assert!(recog.engine().unwrap().name().is_some());
+
+    let ctx = SyncContext::new(&recog).unwrap();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+    ctx.set_enabled(false).unwrap();
+    ctx.set_enabled(true).unwrap();
+
+    sapi_lite::finalize();
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Serve { item: OsString },
+    Leave,
+}
+
+impl Intent for Command {
+    const RULE_NAMES: &'static [&'static str] = &["serve", "leave"];
+
+    fn rule<'a>(name: &str, arena: &'a RuleArena<'a>) -> &'a Rule<'a> {
+        match name {
+            "serve" => arena.sequence(vec![
+                arena.text("serve"),
+                arena.choice(vec![
+                    arena.semantic("tea", arena.text("tea")),
+                    arena.semantic("coffee", arena.text("coffee")),
+                ]),
+            ]),
+            _ => arena.text("leave"),
+        }
+    }
+
+    fn from_phrase(phrase: &Phrase) -> Option<Self> {
+        match phrase.rule.name.as_ref()?.to_str()? {
+            "serve" => Some(Command::Serve {
+                item: phrase.semantics.first()?.value.as_string()?.clone(),
+            }),
+            "leave" => Some(Command::Leave),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test_emulated_intents() {
+    sapi_lite::initialize().unwrap();
+
+    let mut intents = Intents::<Command>::new();
+    intents.register::<Command>();
+
+    let recog = Recognizer::new().unwrap();
+    {
+        let ctx = SyncContext::new(&recog).unwrap();
+        let grammar = intents.build_grammar(&ctx).unwrap();
+        grammar.set_enabled(true).unwrap();
+
+        recog.emulate("serve coffee").unwrap();
+        let intent = ctx.recognize_intent(&intents, TIMEOUT).unwrap().unwrap();
+        assert_eq!(
+            Command::Serve {
+                item: "coffee".into()
+            },
+            intent.intent
+        );
+        assert_eq!(intent.phrase.confidence, intent.confidence);
+    }
+
+    let (tx, rx) = mpsc::sync_channel(1);
+    let ctx = EventfulContext::new(
+        &recog,
+        intents.handler(move |intent| tx.send(intent.intent).unwrap()),
+    )
+    .unwrap();
+    let grammar = intents.build_grammar(&ctx).unwrap();
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("leave").unwrap();
+    assert_eq!(Command::Leave, rx.recv_timeout(TIMEOUT).unwrap());
+
+    sapi_lite::finalize();
+}
+
+#[cfg(feature = "tokio-stt")]
+#[test]
+fn test_emulated_tokio() {
+    use sapi_lite::tokio::UnicastContext;
+
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let (ctx, mut subscriber) = UnicastContext::new(&recog, 1).unwrap();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("have a very good evening").unwrap();
+    let phrase = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(subscriber.recognize())
+        .unwrap();
+    assert_eq!(
+        vec![tree("how_good", vec![leaf(1)]), leaf("pm")],
+        phrase.semantics
+    );
+
+    sapi_lite::finalize();
+}
+
+#[cfg(feature = "futures-stt")]
+#[test]
+fn test_emulated_futures() {
+    use futures::executor::block_on;
+    use futures::StreamExt;
+    use sapi_lite::futures::BroadcastContext;
+
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let (ctx, mut subscriber) = BroadcastContext::new(&recog, 1).unwrap();
+    let mut outcomes = ctx.subscribe();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+
+    recog.emulate("have a very good evening").unwrap();
+    let phrase = block_on(subscriber.recognize()).unwrap();
+    assert_eq!(
+        vec![tree("how_good", vec![leaf(1)]), leaf("pm")],
+        phrase.semantics
+    );
+    match block_on(outcomes.next()) {
+        Some(Recognition::Accepted(phrase)) => assert_eq!("have a very good evening", phrase.text),
+        outcome => panic!("unexpected outcome: {:?}", outcome),
+    }
+
+    drop(ctx);
+    assert_eq!(None, block_on(subscriber.recognize()));
+    assert_eq!(None, block_on(outcomes.next()));
+
+    sapi_lite::finalize();
+}
+
+fn create_grammar(ctx: &Context) -> Grammar {
+    ctx.grammar_builder()
+        .add_rule(&Rule::sequence(
+            &[
+                &Rule::text("have a"),
+                &Rule::semantic(
+                    "how_good",
+                    &Rule::repeat(0..=3, &Rule::semantic(1, &Rule::text("very"))),
+                ),
+                &Rule::text("good"),
+                &Rule::choice(
+                    &[
+                        &Rule::semantic("am", &Rule::text("morning")),
+                        &Rule::semantic("pm", &Rule::text("evening")),
+                    ][..],
+                ),
+            ][..],
+        ))
+        .build()
+        .unwrap()
+}
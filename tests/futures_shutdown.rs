@@ -0,0 +1,39 @@
+use futures::executor::block_on;
+use futures::StreamExt;
+use sapi_lite::futures::{BroadcastContext, UnicastContext};
+use sapi_lite::stt::Recognizer;
+
+#[test]
+fn test_context_without_buffer() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    assert!(UnicastContext::new(&recog, 0).is_err());
+    assert!(BroadcastContext::new(&recog, 0).is_err());
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_context_closed() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let (ctx, mut unicast_sub) = UnicastContext::new(&recog, 1).unwrap();
+    let (broadcast_ctx, mut broadcast_sub) = BroadcastContext::new(&recog, 1).unwrap();
+    let mut hypothesis_sub = broadcast_ctx.hypotheses();
+    ctx.close();
+    broadcast_ctx.close();
+
+    block_on(async {
+        assert_eq!(None, unicast_sub.recognize().await);
+        assert_eq!(None, broadcast_sub.recognize().await);
+        assert_eq!(None, hypothesis_sub.next().await);
+    });
+
+    // A subscriber created after closing the context ends right away
+    let mut late_sub = broadcast_ctx.subscribe();
+    assert_eq!(None, block_on(late_sub.recognize()));
+
+    sapi_lite::finalize();
+}
@@ -0,0 +1,45 @@
+use sapi_lite::stt::{GrammarSize, Recognizer, RuleArena, SyncContext};
+
+#[test]
+fn test_grammar_optimization() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let ctx = SyncContext::new(&recog).unwrap();
+
+    let arena = RuleArena::new();
+    let mut options = Vec::new();
+    for (idx, item) in ["salad", "soup", "salad"].iter().enumerate() {
+        options.push(arena.sequence(vec![
+            arena.text("serve"),
+            arena.semantic(idx as i32, arena.text(*item)),
+            arena.text("to"),
+            arena.text("Arthur"),
+        ]));
+    }
+    let top_rule = arena.choice(options);
+
+    let mut builder = ctx.grammar_builder();
+    builder.add_named_rule("serve", top_rule).optimize(true);
+
+    let report = builder.optimization_report();
+    assert_eq!(
+        GrammarSize {
+            rules: 19,
+            transitions: 30,
+        },
+        report.before
+    );
+    assert_eq!(
+        GrammarSize {
+            rules: 12,
+            transitions: 19,
+        },
+        report.after
+    );
+
+    let grammar = builder.build().unwrap();
+    grammar.set_rule_enabled("serve", true).unwrap();
+
+    sapi_lite::finalize();
+}
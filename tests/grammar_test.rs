@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use sapi_lite::stt::{Recognizer, RuleArena};
+use sapi_lite::testing::{leaf, tree, GrammarTest, TestFailure};
+
+#[test]
+fn test_grammar_harness() {
+    let arena = RuleArena::new();
+    let rule = arena.sequence(vec![
+        arena.text("have a"),
+        arena.semantic(
+            "how_good",
+            arena.repeat(0..=3, arena.semantic(1, arena.text("very"))),
+        ),
+        arena.text("good"),
+        arena.choice(vec![
+            arena.semantic("am", arena.text("morning")),
+            arena.semantic("pm", arena.text("evening")),
+        ]),
+    ]);
+
+    for optimized in [false, true] {
+        GrammarTest::new()
+            .add_rule(rule)
+            .optimize(optimized)
+            .accept(
+                "have a very very good evening",
+                vec![tree("how_good", vec![leaf(1), leaf(1)]), leaf("pm")],
+            )
+            .accept(
+                "Have A Good Morning",
+                vec![tree("how_good", vec![]), leaf("am")],
+            )
+            .reject("have a good night")
+            .reject("have a very very very very good evening")
+            .assert();
+    }
+
+    let failures = GrammarTest::new()
+        .add_rule(rule)
+        .accept("have a good evening", vec![leaf("pm")])
+        .reject("have a good morning")
+        .run()
+        .unwrap_err();
+    assert_eq!(
+        vec![
+            TestFailure {
+                utterance: "have a good evening".to_string(),
+                expected: Some(vec![leaf("pm")]),
+                actual: Some(vec![tree("how_good", vec![]), leaf("pm")]),
+            },
+            TestFailure {
+                utterance: "have a good morning".to_string(),
+                expected: None,
+                actual: Some(vec![tree("how_good", vec![]), leaf("am")]),
+            },
+        ],
+        failures.0
+    );
+    assert_eq!(
+        "\"have a good evening\" produced unexpected semantics (- expected, + actual):\n\
+        + \"how_good\"\n\
+        \x20 \"pm\"\n",
+        failures.0[0].to_string()
+    );
+}
+
+#[test]
+fn test_grammar_harness_emulated() {
+    sapi_lite::initialize().unwrap();
+
+    let arena = RuleArena::new();
+    let rule = arena.sequence(vec![
+        arena.text("serve"),
+        arena.semantic(6, arena.text("salad")),
+        arena.text("to"),
+        arena.semantic("Arthur", arena.text("Arthur")),
+    ]);
+
+    let recog = Recognizer::new().unwrap();
+    GrammarTest::new()
+        .add_named_rule("serve", rule)
+        .accept("serve salad to Arthur", vec![leaf(6), leaf("Arthur")])
+        .reject("serve to Arthur")
+        .assert_emulated(&recog, Duration::from_secs(1));
+
+    sapi_lite::finalize();
+}
@@ -0,0 +1,83 @@
+use sapi_lite::lexicon::{
+    parse_lexicon, write_lexicon, Lexicon, LexiconWord, ParseLexiconError, ParseLexiconErrorKind,
+    PartOfSpeech,
+};
+
+#[test]
+fn test_lexicon_file_round_trip() {
+    let words = vec![
+        LexiconWord::new("Zyxel", "en-US", PartOfSpeech::Noun, "z ay 1 k s eh l"),
+        LexiconWord::new("Zyxel", "en-US", PartOfSpeech::Noun, "z ih 1 k s eh l"),
+        LexiconWord::new(
+            "frobnicate",
+            "en-US",
+            PartOfSpeech::Verb,
+            "f r aa 1 b n ih k ey t",
+        ),
+    ];
+
+    let mut buffer = Vec::new();
+    write_lexicon(&mut buffer, &words).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    assert!(text.starts_with("Zyxel | en-US | noun | z ay 1 k s eh l\n"));
+    assert_eq!(words, parse_lexicon(&text).unwrap());
+}
+
+#[test]
+fn test_lexicon_file_errors() {
+    assert_eq!(
+        Err(ParseLexiconError {
+            line: 3,
+            kind: ParseLexiconErrorKind::WrongFieldCount(3),
+        }),
+        parse_lexicon("# comment\n\nZyxel | en-US | z ay 1 k s eh l")
+    );
+    assert_eq!(
+        Err(ParseLexiconError {
+            line: 1,
+            kind: ParseLexiconErrorKind::UnknownPartOfSpeech("adverb".into()),
+        }),
+        parse_lexicon("quickly | en-US | adverb | k w ih 1 k l iy")
+    );
+    assert_eq!(
+        Err(ParseLexiconError {
+            line: 1,
+            kind: ParseLexiconErrorKind::EmptyField("pronunciation"),
+        }),
+        parse_lexicon("Zyxel | en-US | noun |")
+    );
+
+    let invalid = LexiconWord::new("a|b", "en-US", PartOfSpeech::Noun, "ey");
+    assert!(write_lexicon(Vec::new(), &[invalid]).is_err());
+}
+
+#[test]
+fn test_user_lexicon() {
+    sapi_lite::initialize().unwrap();
+
+    let word = LexiconWord::new("Zyxelian", "en-US", PartOfSpeech::Noun, "z ay 1 k s eh l");
+    let lexicon = Lexicon::new().unwrap();
+    lexicon.add(&word).unwrap();
+    assert!(lexicon.words().unwrap().contains(&word));
+
+    lexicon.remove(&word).unwrap();
+    assert!(!lexicon.words().unwrap().contains(&word));
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_user_lexicon_long_pronunciation() {
+    sapi_lite::initialize().unwrap();
+
+    // Close to the longest pronunciation SAPI accepts, three phones at a time
+    let pronunciation = vec!["aw 1 ch"; 127].join(" ");
+    let word = LexiconWord::new("Ouchouchouch", "en-US", PartOfSpeech::Noun, pronunciation);
+    let lexicon = Lexicon::new().unwrap();
+    lexicon.add(&word).unwrap();
+    assert!(lexicon.words().unwrap().contains(&word));
+
+    lexicon.remove(&word).unwrap();
+
+    sapi_lite::finalize();
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use sapi_lite::stt::Recognizer;
+
+#[test]
+fn test_recognizer_properties() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+
+    recog
+        .set_response_timeout(Duration::from_millis(700))
+        .unwrap();
+    assert_eq!(
+        Duration::from_millis(700),
+        recog.response_timeout().unwrap()
+    );
+    assert!(recog
+        .set_incomplete_response_timeout(Duration::from_secs(11))
+        .is_err());
+
+    recog.set_rejection_threshold(40).unwrap();
+    assert_eq!(40, recog.rejection_threshold().unwrap());
+    assert!(recog.set_high_confidence_threshold(101).is_err());
+
+    sapi_lite::finalize();
+}
@@ -32,6 +32,66 @@ fn test_round_trip() {
         phrase.semantics
     );
 
+    let words: Vec<_> = phrase
+        .elements
+        .iter()
+        .map(|elem| &elem.lexical_form)
+        .collect();
+    assert_eq!(speech.split(' ').collect::<Vec<_>>(), words);
+    for elem in phrase.elements.iter() {
+        assert!(elem.offset >= phrase.offset);
+        assert!(elem.offset + elem.duration <= phrase.offset + phrase.duration);
+    }
+
+    sapi_lite::finalize();
+}
+
+#[cfg(feature = "tokio-stt")]
+#[test]
+fn test_round_trip_audio_levels() {
+    use sapi_lite::stt::EventInterest;
+    use sapi_lite::tokio::UnicastContext;
+
+    sapi_lite::initialize().unwrap();
+
+    let audio_fmt = AudioFormat {
+        sample_rate: SampleRate::Hz8000,
+        bit_rate: BitRate::Bits8,
+        channels: Channels::Mono,
+    };
+    let stream = MemoryStream::new(None).unwrap();
+    speak(
+        "have a good morning",
+        create_output(stream.try_clone().unwrap(), &audio_fmt),
+    );
+
+    let recog = Recognizer::new().unwrap();
+    recog
+        .set_input(create_input(stream, &audio_fmt), false)
+        .unwrap();
+    let (ctx, _subscriber) = UnicastContext::new(&recog, 1).unwrap();
+    ctx.set_interest(EventInterest {
+        audio_level: true,
+        ..Default::default()
+    })
+    .unwrap();
+    let mut levels = ctx.audio_levels();
+    let grammar = create_grammar(&ctx);
+    grammar.set_enabled(true).unwrap();
+
+    let level = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(tokio::time::timeout(
+            Duration::from_secs(1),
+            levels.changed(),
+        ))
+        .unwrap()
+        .unwrap();
+    assert!(level.level <= 100);
+    assert_eq!(level, levels.current());
+
     sapi_lite::finalize();
 }
 
@@ -86,6 +146,7 @@ fn tree<V: Into<SemanticValue<OsString>>>(value: V, children: Vec<SemanticTree>)
     SemanticTree {
         value: value.into(),
         children,
+        confidence: None,
     }
 }
 
@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use sapi_lite::stt::{Context, Grammar, Recognizer, Rule};
+use sapi_lite::tokio::{BroadcastContext, BroadcastResult, HypothesisResult, UnicastContext};
+use tokio::runtime::{Builder, Runtime};
+use tokio::time::timeout;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+#[test]
+fn test_context_dropped_before_await() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let (ctx, mut unicast_sub) = UnicastContext::new(&recog, 1).unwrap();
+    let (broadcast_ctx, mut broadcast_sub) = BroadcastContext::new(&recog, 1).unwrap();
+    let mut hypothesis_sub = broadcast_ctx.hypotheses();
+    let mut audio_level_sub = broadcast_ctx.audio_levels();
+    drop(ctx);
+    drop(broadcast_ctx);
+
+    runtime().block_on(async {
+        assert_eq!(None, unicast_sub.recognize().await);
+        assert_eq!(BroadcastResult::Closed, broadcast_sub.recognize().await);
+        assert_eq!(HypothesisResult::Closed, hypothesis_sub.hypothesis().await);
+        assert_eq!(None, audio_level_sub.changed().await);
+    });
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_context_closed_while_awaiting() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let (ctx, mut unicast_sub) = UnicastContext::new(&recog, 1).unwrap();
+    let (broadcast_ctx, mut broadcast_sub) = BroadcastContext::new(&recog, 1).unwrap();
+
+    runtime().block_on(async {
+        let unicast_task = tokio::spawn(async move { unicast_sub.recognize().await });
+        let broadcast_task = tokio::spawn(async move { broadcast_sub.recognize().await });
+        tokio::task::yield_now().await;
+
+        ctx.close();
+        broadcast_ctx.close();
+        let phrase = timeout(TIMEOUT, unicast_task).await.unwrap().unwrap();
+        assert_eq!(None, phrase);
+        let result = timeout(TIMEOUT, broadcast_task).await.unwrap().unwrap();
+        assert_eq!(BroadcastResult::Closed, result);
+    });
+
+    // A subscriber created after closing the context ends right away
+    let mut late_sub = broadcast_ctx.subscribe();
+    assert_eq!(
+        BroadcastResult::Closed,
+        runtime().block_on(late_sub.recognize())
+    );
+
+    sapi_lite::finalize();
+}
+
+#[test]
+fn test_subscriber_dropped_before_context() {
+    sapi_lite::initialize().unwrap();
+
+    let recog = Recognizer::new().unwrap();
+    let (ctx, unicast_sub) = UnicastContext::new(&recog, 1).unwrap();
+    let (broadcast_ctx, broadcast_sub) = BroadcastContext::new(&recog, 1).unwrap();
+    // Each context gets its own phrase, because the engine delivers a phrase to only one of them
+    let unicast_grammar = create_grammar(&ctx, "hello world");
+    unicast_grammar.set_enabled(true).unwrap();
+    let broadcast_grammar = create_grammar(&broadcast_ctx, "goodbye world");
+    broadcast_grammar.set_enabled(true).unwrap();
+    drop(unicast_sub);
+    drop(broadcast_sub);
+
+    // The phrase recognized without a subscriber is dropped
+    recog.emulate("hello world").unwrap();
+
+    let mut late_sub = broadcast_ctx.subscribe();
+    recog.emulate("goodbye world").unwrap();
+    let rt = runtime();
+    match rt.block_on(timeout(TIMEOUT, late_sub.recognize())).unwrap() {
+        BroadcastResult::Phrase(phrase) => assert_eq!("goodbye world", phrase.text),
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    drop(ctx);
+    drop(broadcast_ctx);
+    assert_eq!(
+        BroadcastResult::Closed,
+        rt.block_on(timeout(TIMEOUT, late_sub.recognize())).unwrap()
+    );
+
+    sapi_lite::finalize();
+}
+
+fn runtime() -> Runtime {
+    Builder::new_current_thread().enable_time().build().unwrap()
+}
+
+fn create_grammar(ctx: &Context, text: &str) -> Grammar {
+    ctx.grammar_builder()
+        .add_rule(&Rule::text(text))
+        .build()
+        .unwrap()
+}